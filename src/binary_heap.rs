@@ -0,0 +1,153 @@
+// https://www.programiz.com/dsa/heap-data-structure
+
+use crate::binary_tree::*;
+
+// Min-heap kept as a complete binary tree: the node payloads (data and name)
+// move during sift-up/sift-down, the tree shape only changes at the last BFS slot.
+#[derive(Default)]
+pub struct MinHeap {
+    pub tree: BinaryTree,
+}
+
+impl MinHeap {
+    pub fn new() -> Self {
+        MinHeap {
+            tree: BinaryTree::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<u32> {
+        self.tree.root.as_ref().map(|root| root.borrow().data)
+    }
+
+    pub fn push(&mut self, data: u32, name: &str) {
+        match self.tree.root.as_ref() {
+            Some(root) => {
+                let node_ref = BinaryTree::insert_complete(root, data, name);
+                Self::sift_up(node_ref);
+            }
+            None => self.tree.root = Some(BinaryTree::new_node_with_data(data, name)),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<(u32, String)> {
+        let root = self.tree.root.clone()?;
+        match BinaryTree::delete_deepest_rightmost(&root) {
+            Some(last) => {
+                // the last node takes the place of the root
+                Self::swap_payload(&root, &last);
+                Self::sift_down(root);
+                let last = last.borrow();
+                Some((last.data, last.name.clone()))
+            }
+            None => {
+                self.tree.root = None;
+                let root = root.borrow();
+                Some((root.data, root.name.clone()))
+            }
+        }
+    }
+
+    fn sift_up(node_ref: BinaryTreeNodeRef) {
+        let mut current = node_ref;
+        loop {
+            let parent = current.borrow().parent.upgrade();
+            match parent {
+                Some(parent) if parent.borrow().data > current.borrow().data => {
+                    Self::swap_payload(&parent, &current);
+                    current = parent;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn sift_down(node_ref: BinaryTreeNodeRef) {
+        let mut current = node_ref;
+        loop {
+            let smallest = {
+                let node = current.borrow();
+                [&node.left, &node.right]
+                    .into_iter()
+                    .flatten()
+                    .min_by_key(|child| child.borrow().data)
+                    .cloned()
+            };
+            match smallest {
+                Some(child) if child.borrow().data < current.borrow().data => {
+                    Self::swap_payload(&current, &child);
+                    current = child;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn swap_payload(a: &BinaryTreeNodeRef, b: &BinaryTreeNodeRef) {
+        let mut a = a.borrow_mut();
+        let mut b = b.borrow_mut();
+        std::mem::swap(&mut a.data, &mut b.data);
+        std::mem::swap(&mut a.name, &mut b.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut heap = MinHeap::new();
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek(), None);
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn push_peek() {
+        let mut heap = MinHeap::new();
+        heap.push(5, "n5");
+        assert_eq!(heap.peek(), Some(5));
+        heap.push(7, "n7");
+        assert_eq!(heap.peek(), Some(5));
+        heap.push(2, "n2");
+        assert_eq!(heap.peek(), Some(2));
+        assert!(!heap.is_empty());
+        assert_eq!(BinaryTree::count(heap.tree.root.as_ref().unwrap()), 3);
+    }
+
+    #[test]
+    fn pop_sorted() {
+        let mut heap = MinHeap::new();
+        // 37 and 101 are coprime, so this is a shuffled 1..=100
+        let values: Vec<u32> = (1..=100).map(|n| n * 37 % 101).collect();
+        for v in &values {
+            heap.push(*v, &format!("n{}", v));
+        }
+
+        let mut popped = Vec::new();
+        while let Some((data, name)) = heap.pop() {
+            assert_eq!(name, format!("n{}", data));
+            popped.push(data);
+        }
+        assert!(heap.is_empty());
+
+        let mut expected = values.clone();
+        expected.sort();
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut heap = MinHeap::new();
+        for v in [3, 1, 3, 1, 2] {
+            heap.push(v, "n");
+        }
+        let popped: Vec<_> = std::iter::from_fn(|| heap.pop().map(|v| v.0)).collect();
+        assert_eq!(popped, [1, 1, 2, 3, 3]);
+    }
+}
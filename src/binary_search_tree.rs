@@ -58,11 +58,7 @@ impl BinarySearchTree {
             }
         }
 
-        if insert_node.is_none() {
-            insert_node = Some(new_node.clone());
-        }
-        else {
-            let node_ref = insert_node.as_ref().unwrap();
+        if let Some(node_ref) = insert_node.as_ref() {
             new_node.borrow_mut().parent = Rc::downgrade(node_ref);
             let mut node = node_ref.borrow_mut();
             if new_node.borrow().data < node.data {
@@ -72,6 +68,9 @@ impl BinarySearchTree {
                 node.right = Some(new_node.clone());
             }
         }
+        else {
+            insert_node = Some(new_node.clone());
+        }
         insert_node
     }
 
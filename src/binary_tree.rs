@@ -1,7 +1,11 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::rc::{Rc, Weak};
+use std::sync::mpsc;
+use std::thread;
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -37,15 +41,135 @@ impl PartialEq for BinaryTreeNode {
 
 impl Eq for BinaryTreeNode {}
 
+impl Hash for BinaryTreeNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TreeError {
+    Cycle,
+    DataLength { expected: usize, found: usize },
+    MissingRoot,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::Cycle => write!(f, "cycle in the parent links"),
+            TreeError::DataLength { expected, found } => {
+                write!(
+                    f,
+                    "expected {} values, one per node, found {}",
+                    expected, found
+                )
+            }
+            TreeError::MissingRoot => write!(f, "a child was added before the root"),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    MismatchedParens { position: usize },
+    InvalidNumber { position: usize },
+    UnexpectedChar { position: usize, found: char },
+    UnexpectedValue { position: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MismatchedParens { position } => {
+                write!(f, "mismatched parentheses at {}", position)
+            }
+            ParseError::InvalidNumber { position } => write!(f, "invalid number at {}", position),
+            ParseError::UnexpectedChar { position, found } => {
+                write!(f, "unexpected '{}' at {}", found, position)
+            }
+            ParseError::UnexpectedValue { position } => {
+                write!(f, "value without a parent at {}", position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GraftError {
+    TargetNotFound,
+}
+
+impl fmt::Display for GraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraftError::TargetNotFound => write!(f, "target node not found"),
+        }
+    }
+}
+
+impl std::error::Error for GraftError {}
+
+#[derive(PartialEq)]
+enum CameraState {
+    Uncovered,
+    Covered,
+    Camera,
+}
+
+// Owned copy of a node payload, for passing nodes across threads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeValue {
+    pub id: Uuid,
+    pub name: String,
+    pub data: u32,
+}
+
+// `Send` copy of the tree shape, `Rc` nodes can't leave their thread
+struct SendNode {
+    value: NodeValue,
+    left: Option<Box<SendNode>>,
+    right: Option<Box<SendNode>>,
+}
+
 pub struct BinaryTree {
     pub root: Option<BinaryTreeNodeRef>,
 }
 
+impl Default for BinaryTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BinaryTree {
+    pub fn new() -> Self {
+        BinaryTree { root: None }
+    }
+
     pub fn with_root(root: BinaryTreeNodeRef) -> Self {
         BinaryTree { root: Some(root) }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // 0 for both an empty tree and a single node, check `is_empty` to tell them apart
+    pub fn depth(&self) -> usize {
+        self.root.as_ref().map(Self::maximum_depth).unwrap_or(0)
+    }
+
     pub fn new_node() -> BinaryTreeNodeRef {
         Rc::new(RefCell::new(BinaryTreeNode {
             id: Uuid::new_v4(),
@@ -57,6 +181,16 @@ impl BinaryTree {
         }))
     }
 
+    pub fn new_node_with_data(data: u32, name: &str) -> BinaryTreeNodeRef {
+        let node_ref = Self::new_node();
+        {
+            let mut node = node_ref.borrow_mut();
+            node.data = data;
+            node.name = name.to_string();
+        }
+        node_ref
+    }
+
     pub fn count(node: &BinaryTreeNodeRef) -> usize {
         let mut count = 0;
         let mut queue = VecDeque::new();
@@ -144,6 +278,102 @@ impl BinaryTree {
         nodes.into()
     }
 
+    // Same order as `flatten_inorder`, but keeps the pending ancestors on a stack
+    // instead of walking back up the parent links, so the parents may be unassigned.
+    pub fn flatten_inorder_stack(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = Some(root);
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node_ref) = current {
+                current = node_ref.borrow().left.clone();
+                stack.push(node_ref);
+            }
+            if let Some(node_ref) = stack.pop() {
+                current = node_ref.borrow().right.clone();
+                nodes.push(node_ref);
+            }
+        }
+        nodes
+    }
+
+    // Counts a `Send` copy of the tree (see `inorder_channel`) on the tokio blocking pool,
+    // the copy itself is made right away on the calling thread. Needs a running tokio runtime.
+    #[cfg(feature = "async")]
+    pub fn count_async(root: BinaryTreeNodeRef) -> impl std::future::Future<Output = usize> {
+        let copy = Self::send_copy(&root);
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let mut count = 0;
+                let mut stack = vec![copy.as_ref()];
+                while let Some(node) = stack.pop() {
+                    count += 1;
+                    stack.extend(node.left.as_deref());
+                    stack.extend(node.right.as_deref());
+                }
+                count
+            })
+            .await
+            // a panic in the counting task is passed on to the caller
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+        }
+    }
+
+    // The nodes can't be sent to another thread as they are (`Rc` is not `Send`),
+    // so the background thread walks a copy of the tree and sends the node values.
+    // The channel has no buffer: each node is visited only once the previous one is received.
+    pub fn inorder_channel(root: BinaryTreeNodeRef) -> mpsc::Receiver<NodeValue> {
+        let copy = Self::send_copy(&root);
+        let (sender, receiver) = mpsc::sync_channel(0);
+        thread::spawn(move || {
+            let mut stack = Vec::new();
+            let mut current = Some(copy.as_ref());
+            while current.is_some() || !stack.is_empty() {
+                while let Some(node) = current {
+                    current = node.left.as_deref();
+                    stack.push(node);
+                }
+                if let Some(node) = stack.pop() {
+                    current = node.right.as_deref();
+                    // the receiver is gone, nobody is interested in the rest
+                    if sender.send(node.value.clone()).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        receiver
+    }
+
+    fn send_copy(node_ref: &BinaryTreeNodeRef) -> Box<SendNode> {
+        let node = node_ref.borrow();
+        Box::new(SendNode {
+            value: NodeValue {
+                id: node.id,
+                name: node.name.clone(),
+                data: node.data,
+            },
+            left: node.left.as_ref().map(Self::send_copy),
+            right: node.right.as_ref().map(Self::send_copy),
+        })
+    }
+
+    // Two stacks: popping from the first one and pushing the left child before the right
+    // collects the nodes in root-right-left order, which is the postorder reversed.
+    pub fn flatten_postorder_stack(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut stack = vec![root];
+        let mut reversed = Vec::new();
+        while let Some(node_ref) = stack.pop() {
+            {
+                let node = node_ref.borrow();
+                stack.extend([&node.left, &node.right].into_iter().flatten().cloned());
+            }
+            reversed.push(node_ref);
+        }
+        reversed.reverse();
+        reversed
+    }
+
     pub fn get_root(node_ref: &BinaryTreeNodeRef) -> BinaryTreeNodeRef {
         let mut start = node_ref.clone();
         while let Some(parent) = start.clone().borrow().parent.upgrade() {
@@ -152,6 +382,36 @@ impl BinaryTree {
         start
     }
 
+    // `get_root` for trees that may be malformed: stops with an error
+    // instead of looping forever when the parent links form a cycle
+    pub fn get_root_safe(node_ref: &BinaryTreeNodeRef) -> Result<BinaryTreeNodeRef, TreeError> {
+        let mut visited = HashSet::new();
+        let mut start = node_ref.clone();
+        visited.insert(start.borrow().id);
+        loop {
+            let parent = start.borrow().parent.upgrade();
+            match parent {
+                Some(parent) => {
+                    if !visited.insert(parent.borrow().id) {
+                        return Err(TreeError::Cycle);
+                    }
+                    start = parent;
+                }
+                None => return Ok(start),
+            }
+        }
+    }
+
+    // 1 for the parent, 2 for the grandparent and so on, 0 is the node itself
+    pub fn kth_ancestor(node: &BinaryTreeNodeRef, k: usize) -> Option<BinaryTreeNodeRef> {
+        let mut current = node.clone();
+        for _ in 0..k {
+            let parent = current.borrow().parent.upgrade()?;
+            current = parent;
+        }
+        Some(current)
+    }
+
     pub fn assign_parents(node: &BinaryTreeNodeRef) {
         let mut queue = VecDeque::new();
         queue.push_back(node.clone());
@@ -168,6 +428,83 @@ impl BinaryTree {
         }
     }
 
+    // Same result as `assign_parents`, but walks the tree depth-first with a stack.
+    // The stack holds at most one pending sibling per level, so it stays O(height),
+    // while the BFS queue grows to a whole level, about n / 2 nodes for a perfect tree.
+    // Wide and bushy trees need less memory this way; on a spine both hold one node.
+    pub fn assign_parents_dfs(node: &BinaryTreeNodeRef) {
+        let mut stack = vec![node.clone()];
+        while let Some(node) = stack.pop() {
+            let n = node.borrow();
+            if let Some(right) = n.right.as_ref() {
+                right.borrow_mut().parent = Rc::downgrade(&node);
+                stack.push(right.clone());
+            }
+            if let Some(left) = n.left.as_ref() {
+                left.borrow_mut().parent = Rc::downgrade(&node);
+                stack.push(left.clone());
+            }
+        }
+    }
+
+    // Every node below the root must have its parent link pointing to the node
+    // holding it as the left or right child. Returns the ids of the nodes failing it.
+    pub fn validate_parents(root: &BinaryTreeNodeRef) -> Result<(), Vec<Uuid>> {
+        let mut invalid = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(root.clone());
+        while let Some(node_ref) = queue.pop_front() {
+            let node = node_ref.borrow();
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                let valid = child
+                    .borrow()
+                    .parent
+                    .upgrade()
+                    .is_some_and(|parent| Self::is_same_node(&parent, &node_ref));
+                if !valid {
+                    invalid.push(child.borrow().id);
+                }
+                queue.push_back(child.clone());
+            }
+        }
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    // The parent links of the nodes below the root can lead to nodes outside of it,
+    // e.g. a detached subtree that still owns one of our nodes' former parents.
+    // Those nodes and everything linked below them as children are reported.
+    pub fn validate_reachable(root: &BinaryTreeNodeRef) -> Result<(), Vec<Uuid>> {
+        let reachable = Self::flatten_top_down(root.clone());
+        let mut visited: HashSet<_> = reachable.iter().map(|n| n.borrow().id).collect();
+
+        let mut dangling = Vec::new();
+        let mut queue: VecDeque<_> = reachable
+            .iter()
+            .filter_map(|n| n.borrow().parent.upgrade())
+            .collect();
+        while let Some(node_ref) = queue.pop_front() {
+            let node = node_ref.borrow();
+            if !visited.insert(node.id) {
+                continue;
+            }
+            dangling.push(node.id);
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back(child.clone());
+            }
+        }
+
+        if dangling.is_empty() {
+            Ok(())
+        } else {
+            Err(dangling)
+        }
+    }
+
     pub fn leftmost(node_ref: &BinaryTreeNodeRef) -> Option<BinaryTreeNodeRef> {
         let mut leftmost = None;
         let mut current = node_ref.clone();
@@ -183,10 +520,16 @@ impl BinaryTree {
         }
     }
 
+    pub fn is_same_node(a: &BinaryTreeNodeRef, b: &BinaryTreeNodeRef) -> bool {
+        a.borrow().id == b.borrow().id
+    }
+
+    // same as `is_same_node`, but also treats two missing nodes as the same
     pub fn is_same(v1: &Option<BinaryTreeNodeRef>, v2: &Option<BinaryTreeNodeRef>) -> bool {
         Self::get_node_id(v1) == Self::get_node_id(v2)
     }
 
+    // only needed for the optional comparison in `is_same`
     fn get_node_id(v: &Option<BinaryTreeNodeRef>) -> Option<Uuid> {
         v.as_ref().map(|node| node.borrow().id)
     }
@@ -225,347 +568,5367 @@ impl BinaryTree {
             node.left = tmp;
         }
     }
-}
-
-pub mod utils {
 
-    use super::*;
-    pub const NODES_COUNT: usize = 15;
+    pub fn clone_subtree(node_ref: &BinaryTreeNodeRef) -> BinaryTreeNodeRef {
+        let root = Self::clone_nodes(node_ref, false);
+        Self::assign_parents(&root);
+        root
+    }
 
-    pub fn populate_node_list() -> Vec<BinaryTreeNodeRef> {
-        let mut list = Vec::<BinaryTreeNodeRef>::with_capacity(NODES_COUNT);
-        (0..NODES_COUNT).for_each(|n| {
-            let node_ref = BinaryTree::new_node();
-            node_ref.borrow_mut().name = format!("n{}", n);
-            node_ref.borrow_mut().data = n as u32;
-            list.push(node_ref)
-        });
-        list
+    pub fn mirror_copy(root: &BinaryTreeNodeRef) -> BinaryTreeNodeRef {
+        let mirror = Self::clone_nodes(root, true);
+        Self::assign_parents(&mirror);
+        mirror
     }
 
-    pub fn populate_balanced_binary_tree() -> BinaryTreeNodeRef {
-        /*
-        node names:
-                     n0
-                /           \
-              n1              n2
-            /    \          /     \
-          n3      n4       n5      n6
-         /   \   /   \   /   \    /   \
-        n7   n8 n9  n10 n11  n12 n13  n14
+    fn clone_nodes(node_ref: &BinaryTreeNodeRef, mirrored: bool) -> BinaryTreeNodeRef {
+        let node = node_ref.borrow();
+        let copy_ref = Self::new_node();
+        {
+            let mut copy = copy_ref.borrow_mut();
+            copy.name = node.name.clone();
+            copy.data = node.data;
 
-        left_child = parent * 2 + 1
-        right_child = parent * 2 + 2 = left_child + 1
-        parent_of_left_child = (left_child - 1)/2
-        parent_of_right_child = (right_child - 2)/2
+            let left = node.left.as_ref().map(|n| Self::clone_nodes(n, mirrored));
+            let right = node.right.as_ref().map(|n| Self::clone_nodes(n, mirrored));
+            if mirrored {
+                copy.left = right;
+                copy.right = left;
+            } else {
+                copy.left = left;
+                copy.right = right;
+            }
+        }
+        copy_ref
+    }
 
-        position:
-        is_left  = (n % 2) != 0
-        is_rignt = (n % 2) == 0
-        */
+    pub fn same_tree(a: &BinaryTreeNodeRef, b: &BinaryTreeNodeRef) -> bool {
+        Self::same_subtree(&Some(a.clone()), &Some(b.clone()))
+    }
 
-        let nodes = populate_node_list();
-        (0..NODES_COUNT).for_each(|n| {
-            let left_child = n * 2 + 1;
-            if left_child < NODES_COUNT {
-                nodes[n].borrow_mut().left = Some(nodes[left_child].clone());
-            }
-            let right_child = left_child + 1;
-            if left_child < NODES_COUNT {
-                nodes[n].borrow_mut().right = Some(nodes[right_child].clone());
+    fn same_subtree(a: &Option<BinaryTreeNodeRef>, b: &Option<BinaryTreeNodeRef>) -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                a.data == b.data
+                    && Self::same_subtree(&a.left, &b.left)
+                    && Self::same_subtree(&a.right, &b.right)
             }
-        });
-        BinaryTree::assign_parents(&nodes[0]);
-        nodes[0].clone()
+            _ => false,
+        }
     }
 
-    pub fn populate_balanced_binary_search_tree() -> BinaryTreeNodeRef {
-        /*
-        node values:
-                       8
-                /             \
-              4                12
-            /    \           /    \
-           2       6       10      14
-         /   \   /  \     /  \    /  \
-        1     3 5    7   9   11  13   15
-        */
+    // compares the data and the shape, like `same_tree` with the sides swapped
+    pub fn are_mirrors(a: &BinaryTreeNodeRef, b: &BinaryTreeNodeRef) -> bool {
+        Self::mirrored_subtree(&Some(a.clone()), &Some(b.clone()), true)
+    }
 
-        let node_values = [8, 4, 12, 2, 6, 10, 14, 1, 3, 5, 7, 9, 11, 13, 15];
+    pub fn height(node_ref: &BinaryTreeNodeRef) -> usize {
+        let node = node_ref.borrow();
+        let left = node.left.as_ref().map(|n| Self::height(n) + 1).unwrap_or(0);
+        let right = node
+            .right
+            .as_ref()
+            .map(|n| Self::height(n) + 1)
+            .unwrap_or(0);
+        left.max(right)
+    }
 
-        let root = populate_balanced_binary_tree();
-        let flatten = BinaryTree::flatten_top_down(root.clone());
-        flatten.iter().enumerate().for_each(|v| {
-            v.1.borrow_mut().data = node_values[v.0];
-        });
-        root
+    pub fn maximum_depth(root: &BinaryTreeNodeRef) -> usize {
+        Self::height(root)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
+    pub fn is_balanced(node_ref: &BinaryTreeNodeRef) -> bool {
+        Self::balanced_height(&Some(node_ref.clone())).is_some()
+    }
 
-    use crate::binary_tree::{utils::*, BinaryTree};
+    // returns the number of levels of a balanced subtree, None if unbalanced
+    fn balanced_height(node: &Option<BinaryTreeNodeRef>) -> Option<usize> {
+        match node {
+            None => Some(0),
+            Some(node_ref) => {
+                let node = node_ref.borrow();
+                let left = Self::balanced_height(&node.left)?;
+                let right = Self::balanced_height(&node.right)?;
+                if left.abs_diff(right) > 1 {
+                    return None;
+                }
+                Some(left.max(right) + 1)
+            }
+        }
+    }
 
-    #[test]
-    fn populate_node_ref_list_test() {
-        let list = populate_node_list();
-        assert_eq!(list.len(), NODES_COUNT);
-        let names: Vec<_> = list.iter().map(|v| v.borrow().name.clone()).collect();
-        (0..NODES_COUNT).for_each(|n| {
-            let name = format!("n{}", n);
-            assert!(names.contains(&name));
-        })
+    pub fn is_valid_bst(node_ref: &BinaryTreeNodeRef) -> bool {
+        Self::is_valid_bst_within(&Some(node_ref.clone()), None, None)
     }
 
-    #[test]
-    fn binary_tree_populate_test1() {
-        let root = populate_balanced_binary_tree();
-        assert_eq!(root.borrow().name, "n0".to_string());
+    fn is_valid_bst_within(
+        node: &Option<BinaryTreeNodeRef>,
+        min: Option<u32>,
+        max: Option<u32>,
+    ) -> bool {
+        match node {
+            None => true,
+            Some(node_ref) => {
+                let node = node_ref.borrow();
+                if min.is_some_and(|min| node.data <= min)
+                    || max.is_some_and(|max| node.data >= max)
+                {
+                    return false;
+                }
+                Self::is_valid_bst_within(&node.left, min, Some(node.data))
+                    && Self::is_valid_bst_within(&node.right, Some(node.data), max)
+            }
+        }
+    }
 
-        let n0 = root.borrow();
-        assert_eq!(n0.parent.upgrade(), None);
+    pub fn rebuild_as_balanced(root: &BinaryTreeNodeRef) -> BinaryTreeNodeRef {
+        let mut nodes = Self::flatten_top_down(root.clone());
+        nodes.sort_by_key(|n| n.borrow().data);
 
-        let n1 = n0.left.as_ref().unwrap().borrow();
-        assert_eq!(n1.name, "n1".to_string());
-        assert_eq!(n1.parent.upgrade().unwrap().borrow().name, "n0".to_string());
+        let balanced = Self::build_balanced(&nodes).unwrap();
+        Self::assign_parents(&balanced);
+        balanced
+    }
 
-        let n2 = n0.right.as_ref().unwrap().borrow();
-        assert_eq!(n2.name, "n2".to_string());
-        assert_eq!(n2.parent.upgrade().unwrap().borrow().name, "n0".to_string());
+    // builds copies of the sorted nodes, keeping their ids
+    fn build_balanced(sorted: &[BinaryTreeNodeRef]) -> Option<BinaryTreeNodeRef> {
+        if sorted.is_empty() {
+            return None;
+        }
 
-        let n3 = n1.left.as_ref().unwrap().borrow();
-        assert_eq!(n3.name, "n3".to_string());
-        assert_eq!(n3.parent.upgrade().unwrap().borrow().name, "n1".to_string());
+        let middle = sorted.len() / 2;
+        let source = sorted[middle].borrow();
+        let node_ref = Rc::new(RefCell::new(BinaryTreeNode {
+            id: source.id,
+            name: source.name.clone(),
+            data: source.data,
+            parent: Weak::new(),
+            left: Self::build_balanced(&sorted[..middle]),
+            right: Self::build_balanced(&sorted[middle + 1..]),
+        }));
+        Some(node_ref)
+    }
 
-        let n4 = n1.right.as_ref().unwrap().borrow();
-        assert_eq!(n4.name, "n4".to_string());
-        assert_eq!(n4.parent.upgrade().unwrap().borrow().name, "n1".to_string());
+    pub fn bst_insert(root: &BinaryTreeNodeRef, data: u32, name: &str) -> BinaryTreeNodeRef {
+        let child = {
+            let node = root.borrow();
+            if data < node.data {
+                node.left.clone()
+            } else {
+                node.right.clone()
+            }
+        };
 
-        let n5 = n2.left.as_ref().unwrap().borrow();
-        assert_eq!(n5.name, "n5".to_string());
-        assert_eq!(n5.parent.upgrade().unwrap().borrow().name, "n2".to_string());
+        match child {
+            Some(child) => Self::bst_insert(&child, data, name),
+            None => {
+                let node_ref = Self::new_node_with_data(data, name);
+                node_ref.borrow_mut().parent = Rc::downgrade(root);
+                let mut node = root.borrow_mut();
+                if data < node.data {
+                    node.left = Some(node_ref.clone());
+                } else {
+                    node.right = Some(node_ref.clone());
+                }
+                node_ref
+            }
+        }
+    }
 
-        let n6 = n2.right.as_ref().unwrap().borrow();
-        assert_eq!(n6.name, "n6".to_string());
-        assert_eq!(n6.parent.upgrade().unwrap().borrow().name, "n2".to_string());
+    // same placement as `bst_insert`, equal data goes to the right
+    pub fn bst_insert_iterative(
+        root: &BinaryTreeNodeRef,
+        data: u32,
+        name: &str,
+    ) -> BinaryTreeNodeRef {
+        let node_ref = Self::new_node_with_data(data, name);
+        let mut current = root.clone();
+        loop {
+            let child = {
+                let node = current.borrow();
+                if data < node.data {
+                    node.left.clone()
+                } else {
+                    node.right.clone()
+                }
+            };
+            match child {
+                Some(child) => current = child,
+                None => break,
+            }
+        }
 
-        let n7 = n3.left.as_ref().unwrap().borrow();
-        assert_eq!(n7.name, "n7".to_string());
-        assert_eq!(n7.parent.upgrade().unwrap().borrow().name, "n3".to_string());
+        node_ref.borrow_mut().parent = Rc::downgrade(&current);
+        let mut parent = current.borrow_mut();
+        if data < parent.data {
+            parent.left = Some(node_ref.clone());
+        } else {
+            parent.right = Some(node_ref.clone());
+        }
+        node_ref
+    }
 
-        let n8 = n3.right.as_ref().unwrap().borrow();
+    // Recursion depth equals the tree height, so a degenerate (list-like) tree
+    // with many nodes can overflow the stack; `count` has no such limit.
+    pub fn count_recursive(node: &BinaryTreeNodeRef) -> usize {
+        let n = node.borrow();
+        1 + n.left.as_ref().map(Self::count_recursive).unwrap_or(0)
+            + n.right.as_ref().map(Self::count_recursive).unwrap_or(0)
+    }
+
+    pub fn paths_of_length_k(root: &BinaryTreeNodeRef, k: usize) -> Vec<Vec<BinaryTreeNodeRef>> {
+        let mut paths = Vec::new();
+        for start in Self::flatten_top_down(root.clone()) {
+            let mut partial = vec![vec![start]];
+            for _ in 0..k {
+                let mut extended = Vec::new();
+                for path in partial {
+                    let last = path.last().unwrap().borrow();
+                    for child in [&last.left, &last.right].into_iter().flatten() {
+                        let mut next = path.clone();
+                        next.push(child.clone());
+                        extended.push(next);
+                    }
+                }
+                partial = extended;
+            }
+            paths.extend(partial);
+        }
+        paths
+    }
+
+    pub fn max_consecutive_sequence(root: &BinaryTreeNodeRef) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(root.clone(), 1)];
+        while let Some((node_ref, length)) = stack.pop() {
+            max = max.max(length);
+            let node = node_ref.borrow();
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                let child_length = if child.borrow().data == node.data + 1 {
+                    length + 1
+                } else {
+                    1
+                };
+                stack.push((child.clone(), child_length));
+            }
+        }
+        max
+    }
+
+    pub fn max_independent_set_sum(root: &BinaryTreeNodeRef) -> u64 {
+        let (with_node, without_node) = Self::independent_set_sums(&Some(root.clone()));
+        with_node.max(without_node)
+    }
+
+    // post-order: (best sum taking the node, best sum skipping the node)
+    fn independent_set_sums(node: &Option<BinaryTreeNodeRef>) -> (u64, u64) {
+        match node {
+            None => (0, 0),
+            Some(node_ref) => {
+                let node = node_ref.borrow();
+                let (left_with, left_without) = Self::independent_set_sums(&node.left);
+                let (right_with, right_without) = Self::independent_set_sums(&node.right);
+                let with_node = node.data as u64 + left_without + right_without;
+                let without_node = left_with.max(left_without) + right_with.max(right_without);
+                (with_node, without_node)
+            }
+        }
+    }
+
+    pub fn flatten_levels(root: &BinaryTreeNodeRef) -> Vec<Vec<BinaryTreeNodeRef>> {
+        let mut levels = Vec::new();
+        let mut level = vec![root.clone()];
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for node_ref in &level {
+                let node = node_ref.borrow();
+                if let Some(left) = node.left.as_ref() {
+                    next.push(left.clone());
+                }
+                if let Some(right) = node.right.as_ref() {
+                    next.push(right.clone());
+                }
+            }
+            levels.push(level);
+            level = next;
+        }
+        levels
+    }
+
+    pub fn level_sums(root: &BinaryTreeNodeRef) -> Vec<u64> {
+        Self::flatten_levels(root)
+            .iter()
+            .map(|level| level.iter().map(|n| n.borrow().data as u64).sum())
+            .collect()
+    }
+
+    pub fn max_level_sum(root: &BinaryTreeNodeRef) -> u64 {
+        Self::level_sums(root).into_iter().max().unwrap_or(0)
+    }
+
+    pub fn two_sum_bst(
+        root: &BinaryTreeNodeRef,
+        target: u32,
+    ) -> Option<(BinaryTreeNodeRef, BinaryTreeNodeRef)> {
+        // two stacks walk the tree inorder (ascending) and reverse inorder (descending)
+        let push_path =
+            |stack: &mut Vec<BinaryTreeNodeRef>, node: Option<BinaryTreeNodeRef>, left: bool| {
+                let mut current = node;
+                while let Some(node_ref) = current {
+                    current = if left {
+                        node_ref.borrow().left.clone()
+                    } else {
+                        node_ref.borrow().right.clone()
+                    };
+                    stack.push(node_ref);
+                }
+            };
+
+        let mut ascending = Vec::new();
+        let mut descending = Vec::new();
+        push_path(&mut ascending, Some(root.clone()), true);
+        push_path(&mut descending, Some(root.clone()), false);
+
+        while let (Some(low), Some(high)) = (ascending.last(), descending.last()) {
+            if Self::is_same_node(low, high) || low.borrow().data > high.borrow().data {
+                break;
+            }
+
+            let sum = low.borrow().data as u64 + high.borrow().data as u64;
+            match sum.cmp(&(target as u64)) {
+                Ordering::Equal => return Some((low.clone(), high.clone())),
+                Ordering::Less => {
+                    let low = ascending.pop().unwrap();
+                    let right = low.borrow().right.clone();
+                    push_path(&mut ascending, right, true);
+                }
+                Ordering::Greater => {
+                    let high = descending.pop().unwrap();
+                    let left = high.borrow().left.clone();
+                    push_path(&mut descending, left, false);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn count_univalue_subtrees(root: &BinaryTreeNodeRef) -> usize {
+        let mut count = 0;
+        Self::is_univalue(root, &mut count);
+        count
+    }
+
+    fn is_univalue(node_ref: &BinaryTreeNodeRef, count: &mut usize) -> bool {
+        let node = node_ref.borrow();
+        let mut univalue = true;
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            // visit both children, even if one of them already failed
+            let child_univalue = Self::is_univalue(child, count);
+            univalue = univalue && child_univalue && child.borrow().data == node.data;
+        }
+        if univalue {
+            *count += 1;
+        }
+        univalue
+    }
+
+    pub fn are_cousins(
+        root: &BinaryTreeNodeRef,
+        a: &BinaryTreeNodeRef,
+        b: &BinaryTreeNodeRef,
+    ) -> bool {
+        let mut found_a = None;
+        let mut found_b = None;
+
+        // (node, parent, depth)
+        let mut queue = VecDeque::new();
+        queue.push_back((root.clone(), None::<BinaryTreeNodeRef>, 0));
+        while let Some((node_ref, parent, depth)) = queue.pop_front() {
+            if Self::is_same_node(&node_ref, a) {
+                found_a = Some((parent.clone(), depth));
+            }
+            if Self::is_same_node(&node_ref, b) {
+                found_b = Some((parent.clone(), depth));
+            }
+            if found_a.is_some() && found_b.is_some() {
+                break;
+            }
+
+            let node = node_ref.borrow();
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((child.clone(), Some(node_ref.clone()), depth + 1));
+            }
+        }
+
+        match (found_a, found_b) {
+            (Some((parent_a, depth_a)), Some((parent_b, depth_b))) => {
+                depth_a == depth_b && !Self::is_same(&parent_a, &parent_b)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn average_of_levels(root: &BinaryTreeNodeRef) -> Vec<f64> {
+        Self::flatten_levels(root)
+            .iter()
+            .map(|level| {
+                let sum: u64 = level.iter().map(|n| n.borrow().data as u64).sum();
+                sum as f64 / level.len() as f64
+            })
+            .collect()
+    }
+
+    pub fn flatten_reverse_inorder(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = Some(root);
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node_ref) = current {
+                current = node_ref.borrow().right.clone();
+                stack.push(node_ref);
+            }
+            if let Some(node_ref) = stack.pop() {
+                current = node_ref.borrow().left.clone();
+                nodes.push(node_ref);
+            }
+        }
+        nodes
+    }
+
+    // on a tie the value closer to the root wins
+    pub fn closest_value(root: &BinaryTreeNodeRef, target: f64) -> u32 {
+        let mut closest = root.borrow().data;
+        let mut current = Some(root.clone());
+        while let Some(node_ref) = current {
+            let node = node_ref.borrow();
+            let data = node.data;
+            if (data as f64 - target).abs() < (closest as f64 - target).abs() {
+                closest = data;
+            }
+            current = if target < data as f64 {
+                node.left.clone()
+            } else if target > data as f64 {
+                node.right.clone()
+            } else {
+                None
+            };
+        }
+        closest
+    }
+
+    // largest first: each node adds its data to the running sum and takes it over,
+    // a sum beyond u32::MAX stays at u32::MAX
+    pub fn to_greater_sum_tree(root: &BinaryTreeNodeRef) {
+        let mut sum: u32 = 0;
+        for node_ref in Self::flatten_reverse_inorder(root.clone()) {
+            let mut node = node_ref.borrow_mut();
+            sum = sum.saturating_add(node.data);
+            node.data = sum;
+        }
+    }
+
+    // the closest values are neighbours in the inorder, u32::MAX when there is no pair at all
+    pub fn min_abs_diff_bst(root: &BinaryTreeNodeRef) -> u32 {
+        let mut min = u32::MAX;
+        let mut previous: Option<u32> = None;
+        for node_ref in Self::flatten_inorder_stack(root.clone()) {
+            let data = node_ref.borrow().data;
+            if let Some(previous) = previous {
+                min = min.min(data.abs_diff(previous));
+            }
+            previous = Some(data);
+        }
+        min
+    }
+
+    pub fn count_in_range(root: &BinaryTreeNodeRef, lo: u32, hi: u32) -> usize {
+        let mut count = 0;
+        let mut stack = vec![root.clone()];
+        while let Some(node_ref) = stack.pop() {
+            let node = node_ref.borrow();
+            if (lo..=hi).contains(&node.data) {
+                count += 1;
+            }
+            // subtrees entirely outside the range are skipped
+            if node.data > lo {
+                if let Some(left) = node.left.as_ref() {
+                    stack.push(left.clone());
+                }
+            }
+            if node.data < hi {
+                if let Some(right) = node.right.as_ref() {
+                    stack.push(right.clone());
+                }
+            }
+        }
+        count
+    }
+
+    pub fn range_sum_bst(root: &BinaryTreeNodeRef, lo: u32, hi: u32) -> u64 {
+        let mut sum = 0;
+        let mut stack = vec![root.clone()];
+        while let Some(node_ref) = stack.pop() {
+            let node = node_ref.borrow();
+            if (lo..=hi).contains(&node.data) {
+                sum += node.data as u64;
+            }
+            // same pruning as in `count_in_range`
+            if node.data > lo {
+                if let Some(left) = node.left.as_ref() {
+                    stack.push(left.clone());
+                }
+            }
+            if node.data < hi {
+                if let Some(right) = node.right.as_ref() {
+                    stack.push(right.clone());
+                }
+            }
+        }
+        sum
+    }
+
+    pub fn largest_bst_subtree(root: &BinaryTreeNodeRef) -> Option<BinaryTreeNodeRef> {
+        let mut largest = None;
+        Self::bst_subtree_info(root, &mut largest);
+        largest.map(|(node_ref, _)| node_ref)
+    }
+
+    // post-order: returns (min, max, size) if the subtree is a valid BST
+    fn bst_subtree_info(
+        node_ref: &BinaryTreeNodeRef,
+        largest: &mut Option<(BinaryTreeNodeRef, usize)>,
+    ) -> Option<(u32, u32, usize)> {
+        let node = node_ref.borrow();
+        let left = node
+            .left
+            .as_ref()
+            .map(|n| Self::bst_subtree_info(n, largest));
+        let right = node
+            .right
+            .as_ref()
+            .map(|n| Self::bst_subtree_info(n, largest));
+
+        let (min, left_size) = match left {
+            None => (node.data, 0),
+            Some(Some((min, max, size))) if max < node.data => (min, size),
+            Some(_) => return None,
+        };
+        let (max, right_size) = match right {
+            None => (node.data, 0),
+            Some(Some((min, max, size))) if min > node.data => (max, size),
+            Some(_) => return None,
+        };
+
+        let size = left_size + right_size + 1;
+        if largest
+            .as_ref()
+            .is_none_or(|(_, largest_size)| size > *largest_size)
+        {
+            *largest = Some((node_ref.clone(), size));
+        }
+        Some((min, max, size))
+    }
+
+    pub fn complete_tree_count(root: &BinaryTreeNodeRef) -> usize {
+        let levels = |is_left: bool| {
+            let mut levels = 0;
+            let mut current = Some(root.clone());
+            while let Some(node_ref) = current {
+                levels += 1;
+                let node = node_ref.borrow();
+                current = if is_left {
+                    node.left.clone()
+                } else {
+                    node.right.clone()
+                };
+            }
+            levels
+        };
+
+        let left_levels = levels(true);
+        if left_levels == levels(false) {
+            // perfect tree
+            return (1 << left_levels) - 1;
+        }
+
+        let node = root.borrow();
+        1 + node
+            .left
+            .as_ref()
+            .map(Self::complete_tree_count)
+            .unwrap_or(0)
+            + node
+                .right
+                .as_ref()
+                .map(Self::complete_tree_count)
+                .unwrap_or(0)
+    }
+
+    // One row per node in top-down order, `side` tells whether a node is the
+    // `left` or the `right` child of its parent and is empty for the root.
+    // The parent id alone can't tell a lone right child from a lone left one,
+    // hence the extra column on top of `id,name,data,parent_id`.
+    // The parent ids come from the traversal, so the parent links may be unset.
+    pub fn to_csv(root: &BinaryTreeNodeRef) -> String {
+        let mut csv = String::from("id,name,data,parent_id,side\n");
+        let mut queue = VecDeque::from([(root.clone(), String::new(), "")]);
+        while let Some((node_ref, parent_id, side)) = queue.pop_front() {
+            let node = node_ref.borrow();
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                node.id,
+                Self::csv_field(&node.name),
+                node.data,
+                parent_id,
+                side
+            ));
+            if let Some(left) = node.left.as_ref() {
+                queue.push_back((left.clone(), node.id.to_string(), "left"));
+            }
+            if let Some(right) = node.right.as_ref() {
+                queue.push_back((right.clone(), node.id.to_string(), "right"));
+            }
+        }
+        csv
+    }
+
+    // Parents have to come before their children, as `to_csv` writes them.
+    pub fn from_csv(csv: &str) -> Result<BinaryTree, String> {
+        let mut records = Self::csv_records(csv).into_iter();
+        match records.next() {
+            Some((_, line)) if line == "id,name,data,parent_id,side" => {}
+            _ => return Err("missing header: id,name,data,parent_id,side".to_string()),
+        }
+
+        let mut root = None;
+        let mut nodes = HashMap::<Uuid, BinaryTreeNodeRef>::new();
+        for (line_number, line) in records {
+            if line.is_empty() {
+                continue;
+            }
+            let fields =
+                Self::split_csv_line(&line).map_err(|e| format!("line {}: {}", line_number, e))?;
+            let [id, name, data, parent_id, side] = fields.as_slice() else {
+                return Err(format!("line {}: expected 5 columns", line_number));
+            };
+
+            let id = Uuid::parse_str(id)
+                .map_err(|e| format!("line {}: invalid id: {}", line_number, e))?;
+            let data = data
+                .parse::<u32>()
+                .map_err(|e| format!("line {}: invalid data: {}", line_number, e))?;
+
+            let node_ref = Self::new_node_with_data(data, name);
+            node_ref.borrow_mut().id = id;
+
+            if parent_id.is_empty() {
+                if !side.is_empty() {
+                    return Err(format!("line {}: root with a side", line_number));
+                }
+                if root.is_some() {
+                    return Err(format!("line {}: more than one root", line_number));
+                }
+                root = Some(node_ref.clone());
+            } else {
+                let parent_id = Uuid::parse_str(parent_id)
+                    .map_err(|e| format!("line {}: invalid parent_id: {}", line_number, e))?;
+                let parent_ref = nodes
+                    .get(&parent_id)
+                    .ok_or(format!("line {}: unknown parent_id", line_number))?;
+                let mut parent = parent_ref.borrow_mut();
+                let child = match side.as_str() {
+                    "left" => &mut parent.left,
+                    "right" => &mut parent.right,
+                    _ => return Err(format!("line {}: invalid side", line_number)),
+                };
+                if child.is_some() {
+                    return Err(format!("line {}: {} child already set", line_number, side));
+                }
+                *child = Some(node_ref.clone());
+            }
+
+            if nodes.insert(id, node_ref).is_some() {
+                return Err(format!("line {}: duplicate id", line_number));
+            }
+        }
+
+        if let Some(root) = root.as_ref() {
+            Self::assign_parents(root);
+        } else if !nodes.is_empty() {
+            return Err("missing root".to_string());
+        }
+        Ok(BinaryTree { root })
+    }
+
+    fn csv_field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    // Splits the input into records along with the line each one starts on.
+    // A line break inside a quoted field belongs to the field.
+    fn csv_records(csv: &str) -> Vec<(usize, String)> {
+        let mut records = Vec::new();
+        let mut record = String::new();
+        let (mut line_number, mut start) = (1, 1);
+        let mut quoted = false;
+        for c in csv.chars() {
+            match c {
+                '\n' if !quoted => {
+                    if record.ends_with('\r') {
+                        record.pop();
+                    }
+                    records.push((start, std::mem::take(&mut record)));
+                    line_number += 1;
+                    start = line_number;
+                    continue;
+                }
+                '\n' => line_number += 1,
+                // a doubled quote toggles twice and stays inside the field
+                '"' => quoted = !quoted,
+                _ => {}
+            }
+            record.push(c);
+        }
+        if !record.is_empty() {
+            records.push((start, record));
+        }
+        records
+    }
+
+    fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        if quoted {
+            return Err("unterminated quote".to_string());
+        }
+        fields.push(field);
+        Ok(fields)
+    }
+
+    pub fn to_xml(root: &BinaryTreeNodeRef) -> String {
+        let node = root.borrow();
+        let mut xml = format!(
+            "<node id=\"{}\" name=\"{}\" data=\"{}\">",
+            node.id,
+            Self::xml_escape(&node.name),
+            node.data
+        );
+        if let Some(left) = node.left.as_ref() {
+            xml.push_str(&format!("<left>{}</left>", Self::to_xml(left)));
+        }
+        if let Some(right) = node.right.as_ref() {
+            xml.push_str(&format!("<right>{}</right>", Self::to_xml(right)));
+        }
+        xml.push_str("</node>");
+        xml
+    }
+
+    pub fn from_xml(xml: &str) -> Result<BinaryTree, String> {
+        let mut input = xml.trim_start();
+        let root = Self::xml_node(&mut input)?;
+        if !input.trim().is_empty() {
+            return Err("unexpected content after the root node".to_string());
+        }
+        Self::assign_parents(&root);
+        Ok(BinaryTree::with_root(root))
+    }
+
+    fn xml_node(input: &mut &str) -> Result<BinaryTreeNodeRef, String> {
+        Self::xml_expect(input, "<node")?;
+
+        let (mut id, mut name, mut data) = (None, None, None);
+        loop {
+            *input = input.trim_start();
+            if let Some(rest) = input.strip_prefix('>') {
+                *input = rest;
+                break;
+            }
+            let (key, rest) = input.split_once("=\"").ok_or("malformed attribute")?;
+            let (value, rest) = rest.split_once('"').ok_or("unterminated attribute")?;
+            *input = rest;
+            let value = Self::xml_unescape(value);
+            match key {
+                "id" => id = Some(Uuid::parse_str(&value).map_err(|e| e.to_string())?),
+                "name" => name = Some(value),
+                "data" => data = Some(value.parse::<u32>().map_err(|e| e.to_string())?),
+                _ => return Err(format!("unknown attribute: {}", key)),
+            }
+        }
+
+        let node_ref = Self::new_node_with_data(
+            data.ok_or("missing attribute: data")?,
+            &name.ok_or("missing attribute: name")?,
+        );
+        node_ref.borrow_mut().id = id.ok_or("missing attribute: id")?;
+
+        *input = input.trim_start();
+        if let Some(rest) = input.strip_prefix("<left>") {
+            *input = rest.trim_start();
+            node_ref.borrow_mut().left = Some(Self::xml_node(input)?);
+            Self::xml_expect(input, "</left>")?;
+        }
+        *input = input.trim_start();
+        if let Some(rest) = input.strip_prefix("<right>") {
+            *input = rest.trim_start();
+            node_ref.borrow_mut().right = Some(Self::xml_node(input)?);
+            Self::xml_expect(input, "</right>")?;
+        }
+        Self::xml_expect(input, "</node>")?;
+        Ok(node_ref)
+    }
+
+    fn xml_expect(input: &mut &str, expected: &str) -> Result<(), String> {
+        match input.trim_start().strip_prefix(expected) {
+            Some(rest) => {
+                *input = rest;
+                Ok(())
+            }
+            None => Err(format!("expected {}", expected)),
+        }
+    }
+
+    fn xml_escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn xml_unescape(value: &str) -> String {
+        value
+            .replace("&quot;", "\"")
+            .replace("&gt;", ">")
+            .replace("&lt;", "<")
+            .replace("&amp;", "&")
+    }
+
+    // Bracket notation, written by `to_bracket_notation` and read by `from_bracket_notation`:
+    // `data(left)(right)`, where a missing child is `()` and trailing missing children
+    // are left out, e.g. `2(1)(3)`, `2()(3)`, `2(1)` and `2`. Parsed nodes are named after
+    // their data.
+    pub fn to_bracket_notation(root: &BinaryTreeNodeRef) -> String {
+        let node = root.borrow();
+        let mut s = node.data.to_string();
+        match (&node.left, &node.right) {
+            (None, None) => {}
+            (Some(left), None) => s += &format!("({})", Self::to_bracket_notation(left)),
+            (left, Some(right)) => {
+                let left = left.as_ref().map(Self::to_bracket_notation);
+                s += &format!(
+                    "({})({})",
+                    left.unwrap_or_default(),
+                    Self::to_bracket_notation(right)
+                );
+            }
+        }
+        s
+    }
+
+    pub fn from_bracket_notation(s: &str) -> Result<BinaryTree, ParseError> {
+        let mut position = 0;
+        let root = Self::bracket_node(s, &mut position)?;
+        match s.as_bytes().get(position) {
+            None => {}
+            Some(b')') => return Err(ParseError::MismatchedParens { position }),
+            Some(_) => return Err(Self::unexpected_char(s, position)),
+        }
+        Self::assign_parents(&root);
+        Ok(BinaryTree::with_root(root))
+    }
+
+    fn bracket_node(s: &str, position: &mut usize) -> Result<BinaryTreeNodeRef, ParseError> {
+        let input = s.as_bytes();
+        let start = *position;
+        if start == input.len() {
+            return Err(ParseError::MismatchedParens { position: start });
+        }
+        while input.get(*position).is_some_and(u8::is_ascii_digit) {
+            *position += 1;
+        }
+        let data = s[start..*position]
+            .parse::<u32>()
+            .map_err(|_| ParseError::InvalidNumber { position: start })?;
+        let node_ref = Self::new_node_with_data(data, &data.to_string());
+
+        for is_left in [true, false] {
+            if input.get(*position) != Some(&b'(') {
+                break;
+            }
+            *position += 1;
+            if input.get(*position) == Some(&b')') {
+                *position += 1;
+                continue;
+            }
+            let child = Self::bracket_node(s, position)?;
+            match input.get(*position) {
+                Some(b')') => *position += 1,
+                None => {
+                    return Err(ParseError::MismatchedParens {
+                        position: *position,
+                    })
+                }
+                Some(_) => return Err(Self::unexpected_char(s, *position)),
+            }
+            let mut node = node_ref.borrow_mut();
+            if is_left {
+                node.left = Some(child);
+            } else {
+                node.right = Some(child);
+            }
+        }
+        Ok(node_ref)
+    }
+
+    fn unexpected_char(s: &str, position: usize) -> ParseError {
+        let found = s[position..].chars().next().unwrap_or_default();
+        ParseError::UnexpectedChar { position, found }
+    }
+
+    // LeetCode style: comma separated BFS values with `null` for missing children,
+    // e.g. `1,2,3,null,null,4,5`. The surrounding brackets are optional.
+    pub fn from_leetcode_str(s: &str) -> Result<BinaryTree, ParseError> {
+        let (mut body, mut start) = (s, 0);
+        if let Some(rest) = s.strip_prefix('[') {
+            body = rest
+                .strip_suffix(']')
+                .ok_or(ParseError::MismatchedParens { position: s.len() })?;
+            start = 1;
+        } else if s.ends_with(']') {
+            return Err(ParseError::MismatchedParens {
+                position: s.len() - 1,
+            });
+        }
+        if body.trim().is_empty() {
+            return Ok(BinaryTree::new());
+        }
+
+        let mut values = Vec::new();
+        for token in body.split(',') {
+            let trimmed = token.trim();
+            let position = start + token.len() - token.trim_start().len();
+            start += token.len() + 1;
+            let value = match trimmed {
+                "null" => None,
+                _ => Some(
+                    trimmed
+                        .parse::<u32>()
+                        .map_err(|_| ParseError::InvalidNumber { position })?,
+                ),
+            };
+            values.push((value, position));
+        }
+
+        let mut values = values.into_iter();
+        let Some((Some(data), _)) = values.next() else {
+            // a null root has no children to take the remaining values
+            return match values.next() {
+                Some((_, position)) => Err(ParseError::UnexpectedValue { position }),
+                None => Ok(BinaryTree::new()),
+            };
+        };
+        let root = Self::new_node_with_data(data, &data.to_string());
+        // every node takes the next two values as its children
+        let mut queue = VecDeque::from([(root.clone(), true)]);
+        for (value, position) in values {
+            let (parent, is_left) = queue
+                .pop_front()
+                .ok_or(ParseError::UnexpectedValue { position })?;
+            if is_left {
+                queue.push_front((parent.clone(), false));
+            }
+            if let Some(data) = value {
+                let child = Self::new_node_with_data(data, &data.to_string());
+                child.borrow_mut().parent = Rc::downgrade(&parent);
+                if is_left {
+                    parent.borrow_mut().left = Some(child.clone());
+                } else {
+                    parent.borrow_mut().right = Some(child.clone());
+                }
+                queue.push_back((child, true));
+            }
+        }
+        Ok(BinaryTree::with_root(root))
+    }
+
+    // trailing nulls are left out, as LeetCode does
+    pub fn to_leetcode_str(root: &BinaryTreeNodeRef) -> String {
+        let mut values = Vec::new();
+        let mut queue = VecDeque::from([Some(root.clone())]);
+        while let Some(node) = queue.pop_front() {
+            match node {
+                Some(node_ref) => {
+                    let node = node_ref.borrow();
+                    values.push(node.data.to_string());
+                    queue.push_back(node.left.clone());
+                    queue.push_back(node.right.clone());
+                }
+                None => values.push("null".to_string()),
+            }
+        }
+        while values.last().is_some_and(|v| v == "null") {
+            values.pop();
+        }
+        values.join(",")
+    }
+
+    pub fn print_level_by_level<W: std::io::Write>(
+        root: &BinaryTreeNodeRef,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        for level in Self::flatten_levels(root) {
+            let names: Vec<_> = level.iter().map(|n| n.borrow().name.clone()).collect();
+            writeln!(out, "{}", names.join(" "))?;
+        }
+        Ok(())
+    }
+
+    // one line with the names from the root down to the parent of the node
+    pub fn print_ancestors<W: std::io::Write>(
+        node: &BinaryTreeNodeRef,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let mut names = Vec::new();
+        let mut current = node.borrow().parent.upgrade();
+        while let Some(parent) = current {
+            names.push(parent.borrow().name.clone());
+            current = parent.borrow().parent.upgrade();
+        }
+        names.reverse();
+        writeln!(out, "{}", names.join(" "))
+    }
+
+    pub fn flatten_preorder(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![root];
+        while let Some(node_ref) = stack.pop() {
+            let node = node_ref.borrow();
+            if let Some(right) = node.right.as_ref() {
+                stack.push(right.clone());
+            }
+            if let Some(left) = node.left.as_ref() {
+                stack.push(left.clone());
+            }
+            nodes.push(node_ref.clone());
+        }
+        nodes
+    }
+
+    pub fn flatten_preorder_morris(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = Vec::new();
+        let mut current = Some(root);
+        while let Some(node_ref) = current {
+            let left = node_ref.borrow().left.clone();
+            let Some(left) = left else {
+                nodes.push(node_ref.clone());
+                current = node_ref.borrow().right.clone();
+                continue;
+            };
+
+            // the rightmost node of the left subtree, or the one already threaded back to us
+            let mut predecessor = left.clone();
+            loop {
+                let right = predecessor.borrow().right.clone();
+                match right {
+                    Some(right) if !Self::is_same_node(&right, &node_ref) => predecessor = right,
+                    _ => break,
+                }
+            }
+
+            if predecessor.borrow().right.is_none() {
+                nodes.push(node_ref.clone());
+                predecessor.borrow_mut().right = Some(node_ref.clone());
+                current = Some(left);
+            } else {
+                // the left subtree is done, remove the thread
+                predecessor.borrow_mut().right = None;
+                current = node_ref.borrow().right.clone();
+            }
+        }
+        nodes
+    }
+
+    pub fn minimum_depth(root: &BinaryTreeNodeRef) -> usize {
+        let mut queue = VecDeque::new();
+        queue.push_back((root.clone(), 0));
+        while let Some((node_ref, depth)) = queue.pop_front() {
+            let node = node_ref.borrow();
+            if node.left.is_none() && node.right.is_none() {
+                return depth;
+            }
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((child.clone(), depth + 1));
+            }
+        }
+        unreachable!("a tree always has at least one leaf")
+    }
+
+    pub fn depth_map(root: &BinaryTreeNodeRef) -> HashMap<Uuid, usize> {
+        let mut depths = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((root.clone(), 0));
+        while let Some((node_ref, depth)) = queue.pop_front() {
+            let node = node_ref.borrow();
+            depths.insert(node.id, depth);
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((child.clone(), depth + 1));
+            }
+        }
+        depths
+    }
+
+    pub fn subtree_size_map(root: &BinaryTreeNodeRef) -> HashMap<Uuid, usize> {
+        let mut sizes = HashMap::new();
+        Self::subtree_sizes(root, &mut sizes);
+        sizes
+    }
+
+    // post-order: the children sizes are known before the parent's
+    fn subtree_sizes(node_ref: &BinaryTreeNodeRef, sizes: &mut HashMap<Uuid, usize>) -> usize {
+        let node = node_ref.borrow();
+        let size = 1 + [&node.left, &node.right]
+            .into_iter()
+            .flatten()
+            .map(|child| Self::subtree_sizes(child, sizes))
+            .sum::<usize>();
+        sizes.insert(node.id, size);
+        size
+    }
+
+    pub fn hld(root: &BinaryTreeNodeRef) -> HldTree {
+        let sizes = Self::subtree_size_map(root);
+        let size = |child: &Option<BinaryTreeNodeRef>| {
+            child.as_ref().map(|n| sizes[&n.borrow().id]).unwrap_or(0)
+        };
+
+        let mut hld = HldTree::default();
+        let root_id = root.borrow().id;
+        // (node, chain head, parent, depth)
+        let mut stack = vec![(root.clone(), root_id, None, 0)];
+        while let Some((node_ref, head, parent, depth)) = stack.pop() {
+            let node = node_ref.borrow();
+            hld.position.insert(node.id, hld.position.len());
+            hld.head.insert(node.id, head);
+            hld.depth.insert(node.id, depth);
+            if let Some(parent) = parent {
+                hld.parent.insert(node.id, parent);
+            }
+
+            // the heavy child goes last on the stack, so it takes the next position
+            // and the chain stays contiguous; a light child starts a chain of its own
+            let (heavy, light) = if size(&node.right) > size(&node.left) {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            if let Some(light) = light {
+                let light_id = light.borrow().id;
+                stack.push((light.clone(), light_id, Some(node.id), depth + 1));
+            }
+            if let Some(heavy) = heavy {
+                stack.push((heavy.clone(), head, Some(node.id), depth + 1));
+            }
+        }
+        hld
+    }
+
+    // A node is recorded when it is entered and again after each of its children,
+    // so the tour has 2n - 1 entries. The LCA of two nodes is the shallowest node
+    // between their first occurrences, a range minimum query over the depths.
+    pub fn euler_tour(root: &BinaryTreeNodeRef) -> (Vec<BinaryTreeNodeRef>, HashMap<Uuid, usize>) {
+        let mut tour = Vec::new();
+        let mut first = HashMap::new();
+        Self::euler_visit(root, &mut tour, &mut first);
+        (tour, first)
+    }
+
+    fn euler_visit(
+        node_ref: &BinaryTreeNodeRef,
+        tour: &mut Vec<BinaryTreeNodeRef>,
+        first: &mut HashMap<Uuid, usize>,
+    ) {
+        let node = node_ref.borrow();
+        first.insert(node.id, tour.len());
+        tour.push(node_ref.clone());
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            Self::euler_visit(child, tour, first);
+            tour.push(node_ref.clone());
+        }
+    }
+
+    // Seen as an undirected graph, the part above a node has n - size nodes. Starting at
+    // the root, that part stays within n / 2 while we only step into a child
+    // bigger than n / 2, and we stop at the first node without one.
+    pub fn centroid(root: &BinaryTreeNodeRef) -> BinaryTreeNodeRef {
+        let sizes = Self::subtree_size_map(root);
+        let half = sizes[&root.borrow().id] / 2;
+
+        let mut current = root.clone();
+        loop {
+            let heavy = {
+                let node = current.borrow();
+                let heavy = [&node.left, &node.right]
+                    .into_iter()
+                    .flatten()
+                    .find(|child| sizes[&child.borrow().id] > half)
+                    .cloned();
+                heavy
+            };
+            match heavy {
+                Some(child) => current = child,
+                None => return current,
+            }
+        }
+    }
+
+    pub fn centroid_decomp(root: &BinaryTreeNodeRef) -> CentroidDecompTree {
+        // the tree as an undirected graph, components are cut at removed centroids
+        let mut neighbours: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for node_ref in Self::flatten_top_down(root.clone()) {
+            let node = node_ref.borrow();
+            neighbours.entry(node.id).or_default();
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                let child_id = child.borrow().id;
+                neighbours.entry(node.id).or_default().push(child_id);
+                neighbours.entry(child_id).or_default().push(node.id);
+            }
+        }
+
+        let mut decomp = CentroidDecompTree::default();
+        // (any node of the component, centroid of the enclosing component)
+        let mut components = vec![(root.borrow().id, None)];
+        while let Some((start, parent)) = components.pop() {
+            let centroid = Self::component_centroid(start, &neighbours, &decomp.level);
+            let level = parent.map(|p| decomp.level[&p] + 1).unwrap_or(0);
+            decomp.level.insert(centroid, level);
+            decomp.parent.insert(centroid, parent);
+            for next in &neighbours[&centroid] {
+                if !decomp.level.contains_key(next) {
+                    components.push((*next, Some(centroid)));
+                }
+            }
+        }
+        decomp
+    }
+
+    fn component_centroid(
+        start: Uuid,
+        neighbours: &HashMap<Uuid, Vec<Uuid>>,
+        removed: &HashMap<Uuid, usize>,
+    ) -> Uuid {
+        // DFS order with the parent of every node, sizes are summed up in reverse
+        let mut order = Vec::new();
+        let mut stack = vec![(start, None)];
+        while let Some((id, parent)) = stack.pop() {
+            order.push((id, parent));
+            for next in &neighbours[&id] {
+                if Some(*next) != parent && !removed.contains_key(next) {
+                    stack.push((*next, Some(id)));
+                }
+            }
+        }
+
+        let mut sizes: HashMap<Uuid, usize> = HashMap::new();
+        let mut largest_child: HashMap<Uuid, usize> = HashMap::new();
+        for (id, parent) in order.iter().rev() {
+            let size = *sizes.entry(*id).or_insert(0) + 1;
+            sizes.insert(*id, size);
+            if let Some(parent) = parent {
+                *sizes.entry(*parent).or_insert(0) += size;
+                let largest = largest_child.entry(*parent).or_insert(0);
+                *largest = (*largest).max(size);
+            }
+        }
+
+        let total = order.len();
+        order
+            .iter()
+            .map(|(id, _)| *id)
+            .find(|id| {
+                let above = total - sizes[id];
+                let below = largest_child.get(id).copied().unwrap_or(0);
+                above.max(below) <= total / 2
+            })
+            .expect("every tree has a centroid")
+    }
+
+    // parents before children, the BFS order is one of them
+    pub fn topological_order(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        Self::flatten_top_down(root.clone())
+    }
+
+    // every node of the tree exactly once, with each node after its parent
+    pub fn is_topological_order(root: &BinaryTreeNodeRef, order: &[BinaryTreeNodeRef]) -> bool {
+        let mut positions = HashMap::new();
+        for (index, node_ref) in order.iter().enumerate() {
+            if positions.insert(node_ref.borrow().id, index).is_some() {
+                return false;
+            }
+        }
+
+        let nodes = Self::flatten_top_down(root.clone());
+        if nodes.len() != order.len() {
+            return false;
+        }
+        nodes.iter().all(|node_ref| {
+            let node = node_ref.borrow();
+            let Some(position) = positions.get(&node.id) else {
+                return false;
+            };
+            let children_after = [&node.left, &node.right]
+                .into_iter()
+                .flatten()
+                .all(|child| positions.get(&child.borrow().id) > Some(position));
+            children_after
+        })
+    }
+
+    // `data` is not unique, so every node is checked, in BFS order
+    pub fn find_all_by_data(root: &BinaryTreeNodeRef, data: u32) -> Vec<BinaryTreeNodeRef> {
+        Self::flatten_top_down(root.clone())
+            .into_iter()
+            .filter(|node_ref| node_ref.borrow().data == data)
+            .collect()
+    }
+
+    pub fn replace_all_by_data(root: &BinaryTreeNodeRef, old_data: u32, new_data: u32) -> usize {
+        let found = Self::find_all_by_data(root, old_data);
+        found
+            .iter()
+            .for_each(|node_ref| node_ref.borrow_mut().data = new_data);
+        found.len()
+    }
+
+    // Every subtree is serialized as (data, left key, right key), where the keys number
+    // the distinct subtrees seen so far, so each one costs O(1) instead of a full string.
+    pub fn find_duplicate_subtrees(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut keys = HashMap::new();
+        let mut seen = HashMap::new();
+        let mut duplicates = Vec::new();
+        Self::subtree_key(&Some(root.clone()), &mut keys, &mut seen, &mut duplicates);
+        duplicates
+    }
+
+    fn subtree_key(
+        node: &Option<BinaryTreeNodeRef>,
+        keys: &mut HashMap<(u32, usize, usize), usize>,
+        seen: &mut HashMap<usize, usize>,
+        duplicates: &mut Vec<BinaryTreeNodeRef>,
+    ) -> usize {
+        // 0 stands for a missing child
+        let Some(node_ref) = node else {
+            return 0;
+        };
+        let node = node_ref.borrow();
+        let left = Self::subtree_key(&node.left, keys, seen, duplicates);
+        let right = Self::subtree_key(&node.right, keys, seen, duplicates);
+
+        let next_key = keys.len() + 1;
+        let key = *keys.entry((node.data, left, right)).or_insert(next_key);
+        let count = seen.entry(key).or_insert(0);
+        *count += 1;
+        // reported once, when the second copy shows up
+        if *count == 2 {
+            duplicates.push(node_ref.clone());
+        }
+        key
+    }
+
+    // a node is good when no node above it on the path from the root holds greater data
+    pub fn count_good_nodes(root: &BinaryTreeNodeRef) -> usize {
+        let mut count = 0;
+        let mut stack = vec![(root.clone(), root.borrow().data)];
+        while let Some((node_ref, max)) = stack.pop() {
+            let node = node_ref.borrow();
+            if node.data >= max {
+                count += 1;
+            }
+            let max = max.max(node.data);
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                stack.push((child.clone(), max));
+            }
+        }
+        count
+    }
+
+    // the largest difference on a path is between its minimum and maximum,
+    // so it is enough to carry both down from the root
+    pub fn max_ancestor_difference(root: &BinaryTreeNodeRef) -> u32 {
+        let data = root.borrow().data;
+        let mut difference = 0;
+        let mut stack = vec![(root.clone(), data, data)];
+        while let Some((node_ref, min, max)) = stack.pop() {
+            let node = node_ref.borrow();
+            let (min, max) = (min.min(node.data), max.max(node.data));
+            difference = difference.max(max - min);
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                stack.push((child.clone(), min, max));
+            }
+        }
+        difference
+    }
+
+    // Assumes as many coins as nodes. Every coin crossing an edge is a move, and the number
+    // crossing the edge above a subtree is its excess: coins minus nodes, negative if short.
+    pub fn distribute_coins_moves(root: &BinaryTreeNodeRef) -> u32 {
+        let mut moves = 0;
+        Self::coins_excess(&Some(root.clone()), &mut moves);
+        moves as u32
+    }
+
+    fn coins_excess(node: &Option<BinaryTreeNodeRef>, moves: &mut u64) -> i64 {
+        let Some(node_ref) = node else {
+            return 0;
+        };
+        let node = node_ref.borrow();
+        let left = Self::coins_excess(&node.left, moves);
+        let right = Self::coins_excess(&node.right, moves);
+        *moves += left.unsigned_abs() + right.unsigned_abs();
+        node.data as i64 + left + right - 1
+    }
+
+    // Bottom-up greedy: a camera goes on the parent of every uncovered node,
+    // the root only takes one when nothing below covers it.
+    pub fn min_cameras(root: &BinaryTreeNodeRef) -> usize {
+        let mut cameras = 0;
+        if Self::camera_state(&Some(root.clone()), &mut cameras) == CameraState::Uncovered {
+            cameras += 1;
+        }
+        cameras
+    }
+
+    fn camera_state(node: &Option<BinaryTreeNodeRef>, cameras: &mut usize) -> CameraState {
+        // a missing child does not need a camera
+        let Some(node_ref) = node else {
+            return CameraState::Covered;
+        };
+        let node = node_ref.borrow();
+        let left = Self::camera_state(&node.left, cameras);
+        let right = Self::camera_state(&node.right, cameras);
+        if left == CameraState::Uncovered || right == CameraState::Uncovered {
+            *cameras += 1;
+            CameraState::Camera
+        } else if left == CameraState::Camera || right == CameraState::Camera {
+            CameraState::Covered
+        } else {
+            CameraState::Uncovered
+        }
+    }
+
+    // From the root down, taking the left child when there is one and the right one
+    // otherwise, up to the first leaf, which is left out. The root is always included.
+    pub fn left_boundary(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        Self::boundary(root, Direction::Left)
+    }
+
+    // same as `left_boundary`, preferring the right child
+    pub fn right_boundary(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        Self::boundary(root, Direction::Right)
+    }
+
+    fn boundary(root: &BinaryTreeNodeRef, direction: Direction) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = vec![root.clone()];
+        let mut current = root.clone();
+        loop {
+            let next = {
+                let node = current.borrow();
+                let (first, second) = match direction {
+                    Direction::Left => (&node.left, &node.right),
+                    Direction::Right => (&node.right, &node.left),
+                };
+                first.as_ref().or(second.as_ref()).cloned()
+            };
+            match next {
+                Some(next) if next.borrow().left.is_some() || next.borrow().right.is_some() => {
+                    nodes.push(next.clone());
+                    current = next;
+                }
+                _ => return nodes,
+            }
+        }
+    }
+
+    // only the shape counts, the data is ignored
+    pub fn is_foldable(root: &BinaryTreeNodeRef) -> bool {
+        let node = root.borrow();
+        Self::mirrored_subtree(&node.left, &node.right, false)
+    }
+
+    fn mirrored_subtree(
+        a: &Option<BinaryTreeNodeRef>,
+        b: &Option<BinaryTreeNodeRef>,
+        compare_data: bool,
+    ) -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                (!compare_data || a.data == b.data)
+                    && Self::mirrored_subtree(&a.left, &b.right, compare_data)
+                    && Self::mirrored_subtree(&a.right, &b.left, compare_data)
+            }
+            _ => false,
+        }
+    }
+
+    // Reverses the data at the odd levels, the nodes stay in place. Meant for
+    // perfect trees, where a level read backwards is the level of the mirrored tree.
+    pub fn reverse_alternate_levels(root: &BinaryTreeNodeRef) {
+        for level in Self::flatten_levels(root).iter().skip(1).step_by(2) {
+            let mut data: Vec<_> = level.iter().map(|n| n.borrow().data).collect();
+            data.reverse();
+            for (node_ref, data) in level.iter().zip(data) {
+                node_ref.borrow_mut().data = data;
+            }
+        }
+    }
+
+    pub fn sum_even_grandparent(root: &BinaryTreeNodeRef) -> u64 {
+        let mut sum = 0;
+        // (node, data of the parent, data of the grandparent)
+        let mut queue = VecDeque::from([(root.clone(), None, None)]);
+        while let Some((node_ref, parent, grandparent)) = queue.pop_front() {
+            let node = node_ref.borrow();
+            if grandparent.is_some_and(|data: u32| data.is_multiple_of(2)) {
+                sum += node.data as u64;
+            }
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                queue.push_back((child.clone(), Some(node.data), parent));
+            }
+        }
+        sum
+    }
+
+    // all the nodes of the last level are leaves
+    pub fn deepest_leaves_sum(root: &BinaryTreeNodeRef) -> u64 {
+        Self::level_sums(root).last().copied().unwrap_or(0)
+    }
+
+    pub fn sum_of_all_depths(root: &BinaryTreeNodeRef) -> usize {
+        Self::depth_map(root).values().sum()
+    }
+
+    pub fn average_depth(root: &BinaryTreeNodeRef) -> f64 {
+        Self::sum_of_all_depths(root) as f64 / Self::count(root) as f64
+    }
+
+    pub fn fill_next_right(root: &BinaryTreeNodeRef, next_map: &mut HashMap<Uuid, Option<Uuid>>) {
+        for level in Self::flatten_levels(root) {
+            for (index, node_ref) in level.iter().enumerate() {
+                let next = level.get(index + 1).map(|n| n.borrow().id);
+                next_map.insert(node_ref.borrow().id, next);
+            }
+        }
+    }
+
+    pub fn flatten_inorder_morris(root: BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut nodes = Vec::new();
+        Self::morris_inorder(root, |node_ref| nodes.push(node_ref.clone()));
+        nodes
+    }
+
+    // Equal values are next to each other in the inorder, so counting runs is enough.
+    // The modes come out in ascending order.
+    pub fn find_mode(root: &BinaryTreeNodeRef) -> Vec<u32> {
+        let mut modes = Vec::new();
+        let (mut previous, mut run, mut max_run) = (None, 0, 0);
+        Self::morris_inorder(root.clone(), |node_ref| {
+            let data = node_ref.borrow().data;
+            run = if previous == Some(data) { run + 1 } else { 1 };
+            previous = Some(data);
+            if run > max_run {
+                max_run = run;
+                modes.clear();
+            }
+            if run == max_run {
+                modes.push(data);
+            }
+        });
+        modes
+    }
+
+    // visits nodes inorder in O(1) extra space by temporarily threading
+    // the rightmost node of each left subtree back to its inorder successor
+    fn morris_inorder(root: BinaryTreeNodeRef, mut visit: impl FnMut(&BinaryTreeNodeRef)) {
+        let mut current = Some(root);
+        while let Some(node_ref) = current {
+            let left = node_ref.borrow().left.clone();
+            let Some(left) = left else {
+                visit(&node_ref);
+                current = node_ref.borrow().right.clone();
+                continue;
+            };
+
+            let mut predecessor = left.clone();
+            loop {
+                let right = predecessor.borrow().right.clone();
+                match right {
+                    Some(right) if !Self::is_same_node(&right, &node_ref) => predecessor = right,
+                    _ => break,
+                }
+            }
+
+            if predecessor.borrow().right.is_none() {
+                predecessor.borrow_mut().right = Some(node_ref.clone());
+                current = Some(left);
+            } else {
+                predecessor.borrow_mut().right = None;
+                visit(&node_ref);
+                current = node_ref.borrow().right.clone();
+            }
+        }
+    }
+
+    pub fn recover_bst(root: &BinaryTreeNodeRef) {
+        let mut previous: Option<BinaryTreeNodeRef> = None;
+        let mut first: Option<BinaryTreeNodeRef> = None;
+        let mut second: Option<BinaryTreeNodeRef> = None;
+
+        Self::morris_inorder(root.clone(), |node_ref| {
+            if let Some(previous) = previous.as_ref() {
+                if previous.borrow().data > node_ref.borrow().data {
+                    if first.is_none() {
+                        first = Some(previous.clone());
+                    }
+                    second = Some(node_ref.clone());
+                }
+            }
+            previous = Some(node_ref.clone());
+        });
+
+        if let (Some(first), Some(second)) = (first, second) {
+            let mut first = first.borrow_mut();
+            let mut second = second.borrow_mut();
+            std::mem::swap(&mut first.data, &mut second.data);
+        }
+    }
+
+    pub fn right_side_view(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        Self::flatten_levels(root)
+            .into_iter()
+            .filter_map(|mut level| level.pop())
+            .collect()
+    }
+
+    pub fn right_view_dfs(root: &BinaryTreeNodeRef) -> Vec<BinaryTreeNodeRef> {
+        let mut view = Vec::new();
+        // right children are visited first, so the first node seen at each depth is the rightmost
+        let mut stack = vec![(root.clone(), 0)];
+        while let Some((node_ref, depth)) = stack.pop() {
+            if depth == view.len() {
+                view.push(node_ref.clone());
+            }
+            let node = node_ref.borrow();
+            if let Some(left) = node.left.as_ref() {
+                stack.push((left.clone(), depth + 1));
+            }
+            if let Some(right) = node.right.as_ref() {
+                stack.push((right.clone(), depth + 1));
+            }
+        }
+        view
+    }
+
+    // Relinks the nodes into a sorted circular list: `left` is the previous node,
+    // `right` the next one. Every pair of neighbours forms an `Rc` cycle, so the caller
+    // has to clear the links of one direction (e.g. all `left` links) to release the nodes.
+    pub fn to_dll(root: &BinaryTreeNodeRef) -> (BinaryTreeNodeRef, BinaryTreeNodeRef) {
+        let mut head = None;
+        let mut tail = None;
+        Self::link_inorder(root, &mut head, &mut tail);
+
+        let head = head.unwrap();
+        let tail = tail.unwrap();
+        head.borrow_mut().left = Some(tail.clone());
+        tail.borrow_mut().right = Some(head.clone());
+        (head, tail)
+    }
+
+    fn link_inorder(
+        node_ref: &BinaryTreeNodeRef,
+        head: &mut Option<BinaryTreeNodeRef>,
+        previous: &mut Option<BinaryTreeNodeRef>,
+    ) {
+        let left = node_ref.borrow().left.clone();
+        if let Some(left) = left {
+            Self::link_inorder(&left, head, previous);
+        }
+
+        match previous.as_ref() {
+            Some(previous) => {
+                previous.borrow_mut().right = Some(node_ref.clone());
+                node_ref.borrow_mut().left = Some(previous.clone());
+            }
+            None => *head = Some(node_ref.clone()),
+        }
+        *previous = Some(node_ref.clone());
+
+        // the successor is not linked yet, so `right` still points to the right subtree
+        let right = node_ref.borrow().right.clone();
+        if let Some(right) = right {
+            Self::link_inorder(&right, head, previous);
+        }
+    }
+
+    pub fn flatten_bottom_up(root: &BinaryTreeNodeRef) -> Vec<Vec<BinaryTreeNodeRef>> {
+        let mut levels = Self::flatten_levels(root);
+        levels.reverse();
+        levels
+    }
+
+    // Empty when the edges don't form a tree over the nodes 0..n
+    pub fn minimum_height_roots(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+        if n == 0 || edges.len() != n - 1 || edges.iter().any(|&(a, b)| a >= n || b >= n) {
+            return Vec::new();
+        }
+
+        let mut neighbours = vec![Vec::new(); n];
+        for &(a, b) in edges {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+        // n - 1 edges connecting all the nodes leave no room for cycles or self-loops
+        let mut reached = vec![false; n];
+        reached[0] = true;
+        let mut stack = vec![0];
+        while let Some(v) = stack.pop() {
+            for &neighbour in &neighbours[v] {
+                if !reached[neighbour] {
+                    reached[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        if reached.contains(&false) {
+            return Vec::new();
+        }
+        if n <= 2 {
+            return (0..n).collect();
+        }
+
+        let mut degrees: Vec<_> = neighbours.iter().map(|v| v.len()).collect();
+
+        // peel off the leaves layer by layer, the last one or two nodes are the centers
+        let mut leaves: Vec<_> = (0..n).filter(|v| degrees[*v] == 1).collect();
+        let mut remaining = n;
+        while remaining > 2 {
+            remaining -= leaves.len();
+            let mut next = Vec::new();
+            for leaf in leaves {
+                for &neighbour in &neighbours[leaf] {
+                    degrees[neighbour] -= 1;
+                    if degrees[neighbour] == 1 {
+                        next.push(neighbour);
+                    }
+                }
+            }
+            leaves = next;
+        }
+        leaves.sort();
+        leaves
+    }
+
+    // The n-th Catalan number. It fits into u64 up to n = 36, larger n panic.
+    pub fn unique_bst_count(n: u32) -> u64 {
+        assert!(n <= 36, "unique_bst_count overflows u64 for n > 36");
+        let n = n as usize;
+        let mut counts = vec![0u64; n + 1];
+        counts[0] = 1;
+        for nodes in 1..=n {
+            // each key in turn is the root, with `root` keys on the left
+            counts[nodes] = (0..nodes)
+                .map(|root| counts[root] * counts[nodes - 1 - root])
+                .sum();
+        }
+        counts[n]
+    }
+
+    // The result grows as the Catalan numbers (429 trees for n = 7, 1430 for n = 8)
+    // and every tree is a separate copy, so n <= 7 is the practical limit.
+    pub fn generate_all_bsts(n: u32) -> Vec<BinaryTree> {
+        Self::generate_bsts(1, n)
+            .into_iter()
+            .map(|root| {
+                if let Some(root) = root.as_ref() {
+                    Self::assign_parents(root);
+                }
+                BinaryTree { root }
+            })
+            .collect()
+    }
+
+    fn generate_bsts(lo: u32, hi: u32) -> Vec<Option<BinaryTreeNodeRef>> {
+        if lo > hi {
+            return vec![None];
+        }
+
+        let mut trees = Vec::new();
+        for key in lo..=hi {
+            let lefts = Self::generate_bsts(lo, key - 1);
+            let rights = Self::generate_bsts(key + 1, hi);
+            for left in &lefts {
+                for right in &rights {
+                    // subtrees are copied, so every tree owns its nodes
+                    let node_ref = Self::new_node_with_data(key, &format!("n{}", key));
+                    {
+                        let mut node = node_ref.borrow_mut();
+                        node.left = left.as_ref().map(Self::clone_subtree);
+                        node.right = right.as_ref().map(Self::clone_subtree);
+                    }
+                    trees.push(Some(node_ref));
+                }
+            }
+        }
+        trees
+    }
+
+    pub fn to_table(root: &BinaryTreeNodeRef) -> String {
+        let name = |node: &Option<BinaryTreeNodeRef>| {
+            node.as_ref()
+                .map(|n| n.borrow().name.clone())
+                .unwrap_or("-".to_string())
+        };
+
+        let mut rows = vec![["name", "data", "parent", "left", "right"].map(String::from)];
+        for node_ref in Self::flatten_top_down(root.clone()) {
+            let node = node_ref.borrow();
+            rows.push([
+                node.name.clone(),
+                node.data.to_string(),
+                name(&node.parent.upgrade()),
+                name(&node.left),
+                name(&node.right),
+            ]);
+        }
+
+        let mut widths = [0; 5];
+        for row in &rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+
+        let format_row = |row: &[String; 5]| {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .map(|(value, width)| format!("{:width$}", value, width = width))
+                .collect();
+            cells.join(" | ").trim_end().to_string()
+        };
+
+        let mut lines = vec![format_row(&rows[0])];
+        lines.push(widths.map(|width| "-".repeat(width)).join("-+-"));
+        lines.extend(rows[1..].iter().map(format_row));
+        lines.join("\n") + "\n"
+    }
+
+    // top-down order, the values wrap around past u32::MAX
+    pub fn assign_incremental_data(root: &BinaryTreeNodeRef, start: u32, step: u32) {
+        for (index, node_ref) in Self::flatten_top_down(root.clone()).iter().enumerate() {
+            node_ref.borrow_mut().data = start.wrapping_add((index as u32).wrapping_mul(step));
+        }
+    }
+
+    pub fn count_all_paths(root: &BinaryTreeNodeRef) -> usize {
+        // every pair of nodes is connected by exactly one simple path
+        let n = Self::count(root);
+        n * (n - 1) / 2
+    }
+
+    // 0 when the node is not in the tree under `root`
+    pub fn count_paths_passing_through(
+        root: &BinaryTreeNodeRef,
+        node: &BinaryTreeNodeRef,
+    ) -> usize {
+        let nodes = Self::flatten_top_down(root.clone());
+        if !nodes.iter().any(|n| Self::is_same_node(n, node)) {
+            return 0;
+        }
+        let n = nodes.len();
+        let (left, right) = {
+            let node = node.borrow();
+            (
+                node.left.as_ref().map(Self::count).unwrap_or(0),
+                node.right.as_ref().map(Self::count).unwrap_or(0),
+            )
+        };
+        // removing the node leaves its two subtrees and the rest of the tree
+        let rest = n - 1 - left - right;
+
+        // paths ending at the node, plus paths joining two of the components
+        (n - 1) + left * right + left * rest + right * rest
+    }
+
+    pub fn trim_bst(root: &BinaryTreeNodeRef, lo: u32, hi: u32) -> Option<BinaryTreeNodeRef> {
+        let trimmed = Self::trimmed_copy(&Some(root.clone()), lo, hi);
+        if let Some(trimmed) = trimmed.as_ref() {
+            Self::assign_parents(trimmed);
+        }
+        trimmed
+    }
+
+    fn trimmed_copy(
+        node: &Option<BinaryTreeNodeRef>,
+        lo: u32,
+        hi: u32,
+    ) -> Option<BinaryTreeNodeRef> {
+        let node_ref = node.as_ref()?;
+        let node = node_ref.borrow();
+        if node.data < lo {
+            return Self::trimmed_copy(&node.right, lo, hi);
+        }
+        if node.data > hi {
+            return Self::trimmed_copy(&node.left, lo, hi);
+        }
+
+        let copy_ref = Self::new_node_with_data(node.data, &node.name);
+        {
+            let mut copy = copy_ref.borrow_mut();
+            copy.left = Self::trimmed_copy(&node.left, lo, hi);
+            copy.right = Self::trimmed_copy(&node.right, lo, hi);
+        }
+        Some(copy_ref)
+    }
+
+    // a copy without the leaves of the original, None if the root is a leaf itself
+    pub fn prune_leaves(root: &BinaryTreeNodeRef) -> Option<BinaryTreeNodeRef> {
+        let pruned = Self::pruned_copy(&Some(root.clone()));
+        if let Some(pruned) = pruned.as_ref() {
+            Self::assign_parents(pruned);
+        }
+        pruned
+    }
+
+    fn pruned_copy(node: &Option<BinaryTreeNodeRef>) -> Option<BinaryTreeNodeRef> {
+        let node_ref = node.as_ref()?;
+        let node = node_ref.borrow();
+        if node.left.is_none() && node.right.is_none() {
+            return None;
+        }
+
+        let copy_ref = Self::new_node_with_data(node.data, &node.name);
+        {
+            let mut copy = copy_ref.borrow_mut();
+            copy.left = Self::pruned_copy(&node.left);
+            copy.right = Self::pruned_copy(&node.right);
+        }
+        Some(copy_ref)
+    }
+
+    // The branch root replaces the child on the given side of the target node,
+    // the replaced subtree is detached from the tree.
+    pub fn graft(
+        tree: &BinaryTree,
+        target_id: Uuid,
+        direction: Direction,
+        branch: BinaryTree,
+    ) -> Result<(), GraftError> {
+        let target = tree
+            .root
+            .as_ref()
+            .and_then(|root| {
+                Self::flatten_top_down(root.clone())
+                    .into_iter()
+                    .find(|n| n.borrow().id == target_id)
+            })
+            .ok_or(GraftError::TargetNotFound)?;
+
+        if let Some(branch_root) = branch.root.as_ref() {
+            branch_root.borrow_mut().parent = Rc::downgrade(&target);
+        }
+        let mut target = target.borrow_mut();
+        let child = match direction {
+            Direction::Left => &mut target.left,
+            Direction::Right => &mut target.right,
+        };
+        if let Some(replaced) = std::mem::replace(child, branch.root) {
+            replaced.borrow_mut().parent = Weak::new();
+        }
+        Ok(())
+    }
+
+    // In place and post-order, so a parent left as a matching leaf goes too.
+    // Returns the root, None when it has been removed as well.
+    pub fn delete_leaves_with_value(
+        root: &BinaryTreeNodeRef,
+        target: u32,
+    ) -> Option<BinaryTreeNodeRef> {
+        let mut node = root.borrow_mut();
+        node.left = node
+            .left
+            .take()
+            .and_then(|left| Self::delete_leaves_with_value(&left, target));
+        node.right = node
+            .right
+            .take()
+            .and_then(|right| Self::delete_leaves_with_value(&right, target));
+
+        if node.left.is_none() && node.right.is_none() && node.data == target {
+            None
+        } else {
+            Some(root.clone())
+        }
+    }
+
+    pub fn insert_complete(root: &BinaryTreeNodeRef, data: u32, name: &str) -> BinaryTreeNodeRef {
+        let node_ref = Self::new_node_with_data(data, name);
+        let mut queue = VecDeque::new();
+        queue.push_back(root.clone());
+        while let Some(parent_ref) = queue.pop_front() {
+            let mut parent = parent_ref.borrow_mut();
+            if parent.left.is_none() {
+                parent.left = Some(node_ref.clone());
+            } else if parent.right.is_none() {
+                parent.right = Some(node_ref.clone());
+            } else {
+                queue.push_back(parent.left.clone().unwrap());
+                queue.push_back(parent.right.clone().unwrap());
+                continue;
+            }
+            node_ref.borrow_mut().parent = Rc::downgrade(&parent_ref);
+            break;
+        }
+        node_ref
+    }
+
+    // The root itself is never removed, so a single node tree gives None.
+    pub fn delete_deepest_rightmost(root: &BinaryTreeNodeRef) -> Option<BinaryTreeNodeRef> {
+        let last = Self::flatten_top_down(root.clone()).pop()?;
+        let parent_ref = last.borrow().parent.upgrade()?;
+        {
+            let mut parent = parent_ref.borrow_mut();
+            if Self::is_same(&parent.right, &Some(last.clone())) {
+                parent.right = None;
+            } else {
+                parent.left = None;
+            }
+        }
+        last.borrow_mut().parent = Weak::new();
+        Some(last)
+    }
+
+    pub fn lca_and_distance(
+        root: &BinaryTreeNodeRef,
+        a: &BinaryTreeNodeRef,
+        b: &BinaryTreeNodeRef,
+    ) -> Option<(BinaryTreeNodeRef, usize)> {
+        let path_a = Self::path_from_root(root, a)?;
+        let path_b = Self::path_from_root(root, b)?;
+
+        // the paths share the nodes down to the lowest common ancestor
+        let common = path_a
+            .iter()
+            .zip(&path_b)
+            .take_while(|(x, y)| Self::is_same_node(x, y))
+            .count();
+        let lca = path_a[common - 1].clone();
+
+        // depth(a) + depth(b) - 2 * depth(lca)
+        let distance = (path_a.len() - 1) + (path_b.len() - 1) - 2 * (common - 1);
+        Some((lca, distance))
+    }
+
+    fn path_from_root(
+        root: &BinaryTreeNodeRef,
+        target: &BinaryTreeNodeRef,
+    ) -> Option<Vec<BinaryTreeNodeRef>> {
+        if Self::is_same_node(root, target) {
+            return Some(vec![root.clone()]);
+        }
+        let node = root.borrow();
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            if let Some(mut path) = Self::path_from_root(child, target) {
+                path.insert(0, root.clone());
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    pub fn has_path_sum(root: &BinaryTreeNodeRef, target: u32) -> bool {
+        let mut stack = vec![(root.clone(), 0u64)];
+        while let Some((node_ref, sum)) = stack.pop() {
+            let node = node_ref.borrow();
+            let sum = sum + node.data as u64;
+            if node.left.is_none() && node.right.is_none() && sum == target as u64 {
+                return true;
+            }
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                stack.push((child.clone(), sum));
+            }
+        }
+        false
+    }
+
+    pub fn paths_with_sum(root: &BinaryTreeNodeRef, target: u32) -> Vec<Vec<BinaryTreeNodeRef>> {
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        Self::collect_paths_with_sum(root, target as u64, &mut path, &mut paths);
+        paths
+    }
+
+    fn collect_paths_with_sum(
+        node_ref: &BinaryTreeNodeRef,
+        remaining: u64,
+        path: &mut Vec<BinaryTreeNodeRef>,
+        paths: &mut Vec<Vec<BinaryTreeNodeRef>>,
+    ) {
+        let node = node_ref.borrow();
+        let Some(remaining) = remaining.checked_sub(node.data as u64) else {
+            return;
+        };
+
+        path.push(node_ref.clone());
+        if node.left.is_none() && node.right.is_none() {
+            if remaining == 0 {
+                paths.push(path.clone());
+            }
+        } else {
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                Self::collect_paths_with_sum(child, remaining, path, paths);
+            }
+        }
+        path.pop();
+    }
+
+    pub fn to_sorted_array(root: &BinaryTreeNodeRef) -> Vec<(u32, String)> {
+        Self::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| (n.borrow().data, n.borrow().name.clone()))
+            .collect()
+    }
+
+    pub fn from_sorted_array(arr: &[(u32, String)]) -> BinaryTree {
+        let root = Self::balanced_from_sorted(arr);
+        if let Some(root) = root.as_ref() {
+            Self::assign_parents(root);
+        }
+        BinaryTree { root }
+    }
+
+    fn balanced_from_sorted(arr: &[(u32, String)]) -> Option<BinaryTreeNodeRef> {
+        if arr.is_empty() {
+            return None;
+        }
+        let middle = arr.len() / 2;
+        let (data, name) = &arr[middle];
+        let node_ref = Self::new_node_with_data(*data, name);
+        {
+            let mut node = node_ref.borrow_mut();
+            node.left = Self::balanced_from_sorted(&arr[..middle]);
+            node.right = Self::balanced_from_sorted(&arr[middle + 1..]);
+        }
+        Some(node_ref)
+    }
+
+    // min-heap ordered by data, the inorder traversal gives back the input sequence
+    pub fn from_cartesian(values: &[(u32, &str)]) -> BinaryTree {
+        let mut stack: Vec<BinaryTreeNodeRef> = Vec::new();
+        for (data, name) in values {
+            let node_ref = Self::new_node_with_data(*data, name);
+
+            // nodes with larger data move into the left subtree of the new node
+            let mut last = None;
+            while let Some(top) = stack.last() {
+                if top.borrow().data <= *data {
+                    break;
+                }
+                last = stack.pop();
+            }
+            node_ref.borrow_mut().left = last;
+            if let Some(top) = stack.last() {
+                top.borrow_mut().right = Some(node_ref.clone());
+            }
+            stack.push(node_ref);
+        }
+
+        let root = stack.first().cloned();
+        if let Some(root) = root.as_ref() {
+            Self::assign_parents(root);
+        }
+        BinaryTree { root }
+    }
+}
+
+impl FromIterator<(u32, String)> for BinaryTree {
+    fn from_iter<I: IntoIterator<Item = (u32, String)>>(iter: I) -> Self {
+        let mut tree = BinaryTree::new();
+        for (data, name) in iter {
+            match tree.root.as_ref() {
+                Some(root) => {
+                    BinaryTree::bst_insert(root, data, &name);
+                }
+                None => tree.root = Some(BinaryTree::new_node_with_data(data, &name)),
+            }
+        }
+        tree
+    }
+}
+
+impl Clone for BinaryTree {
+    fn clone(&self) -> Self {
+        BinaryTree {
+            root: self.root.as_ref().map(BinaryTree::clone_subtree),
+        }
+    }
+}
+
+impl PartialEq for BinaryTree {
+    fn eq(&self, other: &Self) -> bool {
+        BinaryTree::same_subtree(&self.root, &other.root)
+    }
+}
+
+impl Eq for BinaryTree {}
+
+impl IntoIterator for BinaryTree {
+    type Item = BinaryTreeNodeRef;
+    type IntoIter = std::vec::IntoIter<BinaryTreeNodeRef>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self).into_iter()
+    }
+}
+
+impl IntoIterator for &BinaryTree {
+    type Item = BinaryTreeNodeRef;
+    type IntoIter = std::vec::IntoIter<BinaryTreeNodeRef>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.root.as_ref() {
+            // the stack walk doesn't depend on the parent links being set
+            Some(root) => BinaryTree::flatten_inorder_stack(root.clone()).into_iter(),
+            None => Vec::new().into_iter(),
+        }
+    }
+}
+
+// `left` and `right` attach a child to the current node and move into it,
+// `up` moves back to the parent of the current node
+#[derive(Default)]
+pub struct BinaryTreeBuilder {
+    root: Option<BinaryTreeNodeRef>,
+    path: Vec<BinaryTreeNodeRef>,
+    missing_root: bool,
+}
+
+impl BinaryTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(mut self, data: u32, name: &str) -> Self {
+        let root = BinaryTree::new_node_with_data(data, name);
+        self.root = Some(root.clone());
+        self.path = vec![root];
+        self
+    }
+
+    pub fn left(self, data: u32, name: &str) -> Self {
+        self.child(data, name, true)
+    }
+
+    pub fn right(self, data: u32, name: &str) -> Self {
+        self.child(data, name, false)
+    }
+
+    pub fn up(mut self) -> Self {
+        if self.path.len() > 1 {
+            self.path.pop();
+        }
+        self
+    }
+
+    // fails if `left` or `right` was called before `root`
+    pub fn build(self) -> Result<BinaryTree, TreeError> {
+        if self.missing_root {
+            return Err(TreeError::MissingRoot);
+        }
+        if let Some(root) = self.root.as_ref() {
+            BinaryTree::assign_parents(root);
+        }
+        Ok(BinaryTree { root: self.root })
+    }
+
+    fn child(mut self, data: u32, name: &str, is_left: bool) -> Self {
+        let Some(current) = self.path.last() else {
+            self.missing_root = true;
+            return self;
+        };
+        let child = BinaryTree::new_node_with_data(data, name);
+        if is_left {
+            current.borrow_mut().left = Some(child.clone());
+        } else {
+            current.borrow_mut().right = Some(child.clone());
+        }
+        self.path.push(child);
+        self
+    }
+}
+
+// Heavy-light decomposition: each node continues the chain of its parent if it is the child
+// with the larger subtree, and starts a new chain otherwise. A chain occupies consecutive
+// positions, so any root-to-node path crosses O(log n) chains.
+#[derive(Default)]
+pub struct HldTree {
+    head: HashMap<Uuid, Uuid>,
+    position: HashMap<Uuid, usize>,
+    parent: HashMap<Uuid, Uuid>,
+    depth: HashMap<Uuid, usize>,
+    segment_tree: Option<MaxSegmentTree>,
+}
+
+impl HldTree {
+    // the id of the topmost node of the chain holding the node
+    pub fn chain_head(&self, id: Uuid) -> Option<Uuid> {
+        self.head.get(&id).copied()
+    }
+
+    pub fn position(&self, id: Uuid) -> Option<usize> {
+        self.position.get(&id).copied()
+    }
+
+    // `data` holds one value per node, indexed by `position`. It is set here rather than
+    // passed to every `path_query`, so the segment tree over it is built once in O(n)
+    // and not again on each query.
+    pub fn with_data(mut self, data: &[u32]) -> Result<Self, TreeError> {
+        if data.len() != self.position.len() {
+            return Err(TreeError::DataLength {
+                expected: self.position.len(),
+                found: data.len(),
+            });
+        }
+        self.segment_tree = Some(MaxSegmentTree::new(data));
+        Ok(self)
+    }
+
+    // Maximum of the data along the path between two nodes of the tree, both ends included.
+    // None if either node is not in the tree or no data was set with `with_data`.
+    pub fn path_query(&self, a: &BinaryTreeNodeRef, b: &BinaryTreeNodeRef) -> Option<u32> {
+        let segment_tree = self.segment_tree.as_ref()?;
+        let mut a = a.borrow().id;
+        let mut b = b.borrow().id;
+        if !self.position.contains_key(&a) || !self.position.contains_key(&b) {
+            return None;
+        }
+
+        let mut max = 0;
+        while self.head[&a] != self.head[&b] {
+            if self.depth[&self.head[&a]] < self.depth[&self.head[&b]] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let head = self.head[&a];
+            max = max.max(segment_tree.query(self.position[&head], self.position[&a]));
+            a = self.parent[&head];
+        }
+
+        let (from, to) = (self.position[&a], self.position[&b]);
+        Some(max.max(segment_tree.query(from.min(to), from.max(to))))
+    }
+}
+
+// The centroid of the whole tree is the root of the centroid tree, the centroids of the
+// components left after removing it are its children, and so on. Every component is at
+// most half of the enclosing one, so the centroid tree has O(log n) levels.
+#[derive(Default)]
+pub struct CentroidDecompTree {
+    parent: HashMap<Uuid, Option<Uuid>>,
+    level: HashMap<Uuid, usize>,
+}
+
+impl CentroidDecompTree {
+    // the centroid of the enclosing component, None for the top centroid
+    pub fn parent(&self, node_id: Uuid) -> Option<Uuid> {
+        self.parent.get(&node_id).copied().flatten()
+    }
+
+    // 0 for the centroid of the whole tree, panics for ids not in the tree
+    pub fn level(&self, node_id: Uuid) -> usize {
+        self.level[&node_id]
+    }
+}
+
+// Bottom-up segment tree answering maximum queries over inclusive ranges
+struct MaxSegmentTree {
+    len: usize,
+    nodes: Vec<u32>,
+}
+
+impl MaxSegmentTree {
+    fn new(data: &[u32]) -> Self {
+        let len = data.len();
+        let mut nodes = vec![0; 2 * len];
+        nodes[len..].copy_from_slice(data);
+        for i in (1..len).rev() {
+            nodes[i] = nodes[2 * i].max(nodes[2 * i + 1]);
+        }
+        MaxSegmentTree { len, nodes }
+    }
+
+    fn query(&self, from: usize, to: usize) -> u32 {
+        let mut max = 0;
+        let mut left = from + self.len;
+        let mut right = to + self.len + 1;
+        while left < right {
+            if left % 2 == 1 {
+                max = max.max(self.nodes[left]);
+                left += 1;
+            }
+            if right % 2 == 1 {
+                right -= 1;
+                max = max.max(self.nodes[right]);
+            }
+            left /= 2;
+            right /= 2;
+        }
+        max
+    }
+}
+
+pub mod utils {
+
+    use super::*;
+    pub const NODES_COUNT: usize = 15;
+
+    pub fn populate_node_list() -> Vec<BinaryTreeNodeRef> {
+        let mut list = Vec::<BinaryTreeNodeRef>::with_capacity(NODES_COUNT);
+        (0..NODES_COUNT)
+            .for_each(|n| list.push(BinaryTree::new_node_with_data(n as u32, &format!("n{}", n))));
+        list
+    }
+
+    pub fn populate_right_spine(count: usize) -> BinaryTreeNodeRef {
+        /*
+        node names:
+        n0
+          \
+           n1
+             \
+              ...
+        */
+
+        let nodes: Vec<_> = (0..count)
+            .map(|n| BinaryTree::new_node_with_data(n as u32, &format!("n{}", n)))
+            .collect();
+        nodes.windows(2).for_each(|v| {
+            v[0].borrow_mut().right = Some(v[1].clone());
+        });
+        BinaryTree::assign_parents(&nodes[0]);
+        nodes[0].clone()
+    }
+
+    pub fn populate_balanced_binary_tree() -> BinaryTreeNodeRef {
+        /*
+        node names:
+                     n0
+                /           \
+              n1              n2
+            /    \          /     \
+          n3      n4       n5      n6
+         /   \   /   \   /   \    /   \
+        n7   n8 n9  n10 n11  n12 n13  n14
+
+        left_child = parent * 2 + 1
+        right_child = parent * 2 + 2 = left_child + 1
+        parent_of_left_child = (left_child - 1)/2
+        parent_of_right_child = (right_child - 2)/2
+
+        position:
+        is_left  = (n % 2) != 0
+        is_rignt = (n % 2) == 0
+        */
+
+        let nodes = populate_node_list();
+        (0..NODES_COUNT).for_each(|n| {
+            let left_child = n * 2 + 1;
+            if left_child < NODES_COUNT {
+                nodes[n].borrow_mut().left = Some(nodes[left_child].clone());
+            }
+            let right_child = left_child + 1;
+            if left_child < NODES_COUNT {
+                nodes[n].borrow_mut().right = Some(nodes[right_child].clone());
+            }
+        });
+        BinaryTree::assign_parents(&nodes[0]);
+        nodes[0].clone()
+    }
+
+    pub fn populate_balanced_binary_search_tree() -> BinaryTreeNodeRef {
+        /*
+        node values:
+                       8
+                /             \
+              4                12
+            /    \           /    \
+           2       6       10      14
+         /   \   /  \     /  \    /  \
+        1     3 5    7   9   11  13   15
+        */
+
+        let node_values = [8, 4, 12, 2, 6, 10, 14, 1, 3, 5, 7, 9, 11, 13, 15];
+
+        let root = populate_balanced_binary_tree();
+        let flatten = BinaryTree::flatten_top_down(root.clone());
+        flatten.iter().enumerate().for_each(|v| {
+            v.1.borrow_mut().data = node_values[v.0];
+        });
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{utils::*, *};
+
+    #[test]
+    fn populate_node_ref_list_test() {
+        let list = populate_node_list();
+        assert_eq!(list.len(), NODES_COUNT);
+        let names: Vec<_> = list.iter().map(|v| v.borrow().name.clone()).collect();
+        (0..NODES_COUNT).for_each(|n| {
+            let name = format!("n{}", n);
+            assert!(names.contains(&name));
+        })
+    }
+
+    #[test]
+    fn binary_tree_populate_test1() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(root.borrow().name, "n0".to_string());
+
+        let n0 = root.borrow();
+        assert_eq!(n0.parent.upgrade(), None);
+
+        let n1 = n0.left.as_ref().unwrap().borrow();
+        assert_eq!(n1.name, "n1".to_string());
+        assert_eq!(n1.parent.upgrade().unwrap().borrow().name, "n0".to_string());
+
+        let n2 = n0.right.as_ref().unwrap().borrow();
+        assert_eq!(n2.name, "n2".to_string());
+        assert_eq!(n2.parent.upgrade().unwrap().borrow().name, "n0".to_string());
+
+        let n3 = n1.left.as_ref().unwrap().borrow();
+        assert_eq!(n3.name, "n3".to_string());
+        assert_eq!(n3.parent.upgrade().unwrap().borrow().name, "n1".to_string());
+
+        let n4 = n1.right.as_ref().unwrap().borrow();
+        assert_eq!(n4.name, "n4".to_string());
+        assert_eq!(n4.parent.upgrade().unwrap().borrow().name, "n1".to_string());
+
+        let n5 = n2.left.as_ref().unwrap().borrow();
+        assert_eq!(n5.name, "n5".to_string());
+        assert_eq!(n5.parent.upgrade().unwrap().borrow().name, "n2".to_string());
+
+        let n6 = n2.right.as_ref().unwrap().borrow();
+        assert_eq!(n6.name, "n6".to_string());
+        assert_eq!(n6.parent.upgrade().unwrap().borrow().name, "n2".to_string());
+
+        let n7 = n3.left.as_ref().unwrap().borrow();
+        assert_eq!(n7.name, "n7".to_string());
+        assert_eq!(n7.parent.upgrade().unwrap().borrow().name, "n3".to_string());
+
+        let n8 = n3.right.as_ref().unwrap().borrow();
         assert_eq!(n8.name, "n8".to_string());
         assert_eq!(n8.parent.upgrade().unwrap().borrow().name, "n3".to_string());
 
-        let n9 = n4.left.as_ref().unwrap().borrow();
-        assert_eq!(n9.name, "n9".to_string());
-        assert_eq!(n9.parent.upgrade().unwrap().borrow().name, "n4".to_string());
+        let n9 = n4.left.as_ref().unwrap().borrow();
+        assert_eq!(n9.name, "n9".to_string());
+        assert_eq!(n9.parent.upgrade().unwrap().borrow().name, "n4".to_string());
+
+        let n10 = n4.right.as_ref().unwrap().borrow();
+        assert_eq!(n10.name, "n10".to_string());
+        assert_eq!(
+            n10.parent.upgrade().unwrap().borrow().name,
+            "n4".to_string()
+        );
+
+        let n11 = n5.left.as_ref().unwrap().borrow();
+        assert_eq!(n11.name, "n11".to_string());
+        assert_eq!(
+            n11.parent.upgrade().unwrap().borrow().name,
+            "n5".to_string()
+        );
+
+        let n12 = n5.right.as_ref().unwrap().borrow();
+        assert_eq!(n12.name, "n12".to_string());
+        assert_eq!(
+            n12.parent.upgrade().unwrap().borrow().name,
+            "n5".to_string()
+        );
+
+        let n13 = n6.left.as_ref().unwrap().borrow();
+        assert_eq!(n13.name, "n13".to_string());
+        assert_eq!(
+            n13.parent.upgrade().unwrap().borrow().name,
+            "n6".to_string()
+        );
+
+        let n14 = n6.right.as_ref().unwrap().borrow();
+        assert_eq!(n14.name, "n14".to_string());
+        assert_eq!(
+            n14.parent.upgrade().unwrap().borrow().name,
+            "n6".to_string()
+        );
+    }
+
+    #[test]
+    fn binary_tree_populate_test2() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root);
+        let nodes_count = nodes.len();
+        assert_eq!(nodes_count, NODES_COUNT);
+
+        for (index, node_ref) in nodes.iter().enumerate() {
+            let node = node_ref.borrow();
+            assert_eq!(node.name, format!("n{index}"));
+            if index == 0 {
+                assert_eq!(node.parent.upgrade(), None);
+            } else {
+                let parent_position = if index % 2 != 0 { 1 } else { 2 };
+                let parent = (index - parent_position) / 2;
+                assert_eq!(
+                    node.parent.upgrade().unwrap().borrow().name,
+                    format!("n{}", parent)
+                );
+            }
+            let left = index * 2 + 1;
+            if left < nodes_count {
+                assert_eq!(
+                    node.left.as_ref().unwrap().borrow().name,
+                    format!("n{}", left)
+                );
+            }
+            let right = left + 1;
+            if left < nodes_count {
+                assert_eq!(
+                    node.right.as_ref().unwrap().borrow().name,
+                    format!("n{}", right)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn count() {
+        let node = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::count(&node), NODES_COUNT);
+    }
+
+    #[test]
+    fn flatten_top_down() {
+        let root = populate_balanced_binary_search_tree();
+
+        let flatten_nodes = BinaryTree::flatten_top_down(root);
+        assert_eq!(flatten_nodes.len(), NODES_COUNT);
+
+        let flatten_names: Vec<_> = flatten_nodes
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        let expected_names = (0..NODES_COUNT)
+            .map(|n| format!("n{}", n))
+            .collect::<Vec<_>>();
+        assert_eq!(flatten_names, expected_names);
+    }
+
+    #[test]
+    fn leftmost() {
+        let expected = HashMap::from([
+            ("n0", Some("n7")),
+            ("n1", Some("n7")),
+            ("n2", Some("n11")),
+            ("n3", Some("n7")),
+            ("n4", Some("n9")),
+            ("n5", Some("n11")),
+            ("n6", Some("n13")),
+            ("n7", None),
+            ("n8", None),
+            ("n9", None),
+            ("n10", None),
+            ("n11", None),
+            ("n12", None),
+            ("n13", None),
+            ("n14", None),
+        ]);
+
+        let root = populate_balanced_binary_tree();
+        let flatten_nodes = BinaryTree::flatten_top_down(root);
+        assert_eq!(flatten_nodes.len(), expected.len());
+        let mut flatten_names: Vec<_> = flatten_nodes
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        let mut expected_names = expected.iter().map(|v| v.0.to_string()).collect::<Vec<_>>();
+        flatten_names.sort();
+        expected_names.sort();
+        assert_eq!(flatten_names, expected_names);
+
+        for node_ref in flatten_nodes {
+            let node = node_ref.borrow();
+            let leftmost = BinaryTree::leftmost(&node_ref);
+            let name;
+            assert_eq!(
+                expected[node.name.as_str()],
+                match leftmost {
+                    Some(left_ref) => {
+                        let node = left_ref.borrow();
+                        name = node.name.clone();
+                        Some(name.as_str())
+                    }
+                    None => None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn flatten_inorder() {
+        let expected = [
+            "n7", "n3", "n8", "n1", "n9", "n4", "n10", "n0", "n11", "n5", "n12", "n2", "n13", "n6",
+            "n14",
+        ];
+
+        let root = populate_balanced_binary_tree();
+        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
+        assert_eq!(flatten_nodes.len(), expected.len());
+
+        let flatten_names: Vec<_> = flatten_nodes
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(flatten_names, expected);
+    }
+
+    #[test]
+    fn invert_recursive() {
+        let expected = [
+            "n14", "n6", "n13", "n2", "n12", "n5", "n11", "n0", "n10", "n4", "n9", "n1", "n8",
+            "n3", "n7",
+        ];
+
+        let root = populate_balanced_binary_tree();
+        BinaryTree::invert_recursive(&root);
+
+        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
+        assert_eq!(flatten_nodes.len(), expected.len());
+
+        let flatten_names: Vec<_> = flatten_nodes
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(flatten_names, expected);
+    }
+
+    #[test]
+    fn invert_iterative() {
+        let expected = [
+            "n14", "n6", "n13", "n2", "n12", "n5", "n11", "n0", "n10", "n4", "n9", "n1", "n8",
+            "n3", "n7",
+        ];
+
+        let root = populate_balanced_binary_tree();
+        BinaryTree::invert_iterative(root.clone());
+
+        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
+        assert_eq!(flatten_nodes.len(), expected.len());
+
+        let flatten_names: Vec<_> = flatten_nodes
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(flatten_names, expected);
+    }
+
+    #[test]
+    fn clone_subtree() {
+        let root = populate_balanced_binary_search_tree();
+        let copy = BinaryTree::clone_subtree(&root);
+        assert!(BinaryTree::same_tree(&root, &copy));
+        assert_ne!(root.borrow().id, copy.borrow().id);
+
+        copy.borrow_mut().data = 100;
+        assert_eq!(root.borrow().data, 8);
+        assert!(!BinaryTree::same_tree(&root, &copy));
+    }
+
+    #[test]
+    fn mirror_copy() {
+        let expected = [
+            "n14", "n6", "n13", "n2", "n12", "n5", "n11", "n0", "n10", "n4", "n9", "n1", "n8",
+            "n3", "n7",
+        ];
+
+        let root = populate_balanced_binary_tree();
+        let original_names: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        let mirror = BinaryTree::mirror_copy(&root);
+        assert!(!BinaryTree::same_tree(&root, &mirror));
+
+        let mirror_names: Vec<_> = BinaryTree::flatten_inorder(mirror)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(mirror_names, expected);
+
+        let names: Vec<_> = BinaryTree::flatten_inorder(root)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, original_names);
+    }
+
+    #[test]
+    fn height() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::height(&root), 3);
+        assert_eq!(BinaryTree::height(&BinaryTree::new_node()), 0);
+    }
+
+    #[test]
+    fn is_balanced() {
+        let root = populate_balanced_binary_tree();
+        assert!(BinaryTree::is_balanced(&root));
+
+        let mut spine = None;
+        for node_ref in populate_node_list().iter().take(3) {
+            node_ref.borrow_mut().right = spine;
+            spine = Some(node_ref.clone());
+        }
+        assert!(!BinaryTree::is_balanced(&spine.unwrap()));
+    }
+
+    #[test]
+    fn is_valid_bst() {
+        let root = populate_balanced_binary_search_tree();
+        assert!(BinaryTree::is_valid_bst(&root));
+
+        let root = populate_balanced_binary_tree();
+        assert!(!BinaryTree::is_valid_bst(&root));
+    }
+
+    #[test]
+    fn rebuild_as_balanced() {
+        let root = populate_balanced_binary_tree();
+        let original_ids: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| (n.borrow().id, n.borrow().name.clone(), n.borrow().data))
+            .collect();
+
+        let balanced = BinaryTree::rebuild_as_balanced(&root);
+        assert!(BinaryTree::is_valid_bst(&balanced));
+        assert!(BinaryTree::is_balanced(&balanced));
+        assert_eq!(BinaryTree::count(&balanced), NODES_COUNT);
+
+        let rebuilt_ids: Vec<_> = BinaryTree::flatten_inorder(balanced)
+            .iter()
+            .map(|n| (n.borrow().id, n.borrow().name.clone(), n.borrow().data))
+            .collect();
+        let mut expected_ids = original_ids.clone();
+        expected_ids.sort_by_key(|v| v.2);
+        assert_eq!(rebuilt_ids, expected_ids);
+
+        // the original tree is left untouched
+        let ids: Vec<_> = BinaryTree::flatten_top_down(root)
+            .iter()
+            .map(|n| (n.borrow().id, n.borrow().name.clone(), n.borrow().data))
+            .collect();
+        assert_eq!(ids, original_ids);
+    }
+
+    #[test]
+    fn assign_parents_dfs() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let parent_names = |nodes: &[BinaryTreeNodeRef]| {
+            nodes
+                .iter()
+                .map(|n| n.borrow().parent.upgrade().map(|p| p.borrow().name.clone()))
+                .collect::<Vec<_>>()
+        };
+        let expected = parent_names(&nodes);
+
+        nodes
+            .iter()
+            .for_each(|n| n.borrow_mut().parent = Weak::new());
+        BinaryTree::assign_parents_dfs(&root);
+        assert_eq!(parent_names(&nodes), expected);
+    }
+
+    #[test]
+    fn new_node_with_data() {
+        let node_ref = BinaryTree::new_node_with_data(42, "n42");
+        let node = node_ref.borrow();
+        assert_eq!(node.data, 42);
+        assert_eq!(node.name, "n42".to_string());
+        assert_eq!(node.parent.upgrade(), None);
+        assert!(node.left.is_none());
+        assert!(node.right.is_none());
+    }
+
+    #[test]
+    fn new_is_empty() {
+        let tree = BinaryTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn with_root_is_not_empty() {
+        let root = populate_balanced_binary_tree();
+        let tree = BinaryTree::with_root(root.clone());
+        assert!(!tree.is_empty());
+        assert_eq!(BinaryTree::count(tree.root.as_ref().unwrap()), NODES_COUNT);
+    }
+
+    #[test]
+    fn default_is_empty() {
+        assert!(BinaryTree::default().is_empty());
+        assert!(BinaryTree::default().root.is_none());
+    }
+
+    #[test]
+    fn bst_insert() {
+        let root = BinaryTree::new_node_with_data(8, "n8");
+        for data in [4, 12, 2, 6, 10, 14] {
+            let node_ref = BinaryTree::bst_insert(&root, data, &format!("n{}", data));
+            assert_eq!(node_ref.borrow().data, data);
+            assert!(node_ref.borrow().parent.upgrade().is_some());
+        }
+        assert_eq!(BinaryTree::count(&root), 7);
+        assert!(BinaryTree::is_valid_bst(&root));
+    }
+
+    #[test]
+    fn from_iterator() {
+        let values = [7, 3, 12, 1, 9, 15, 5, 2, 14, 8, 11, 4, 13, 6, 10];
+
+        let tree: BinaryTree = values.iter().map(|v| (*v, format!("n{}", v))).collect();
+        let root = tree.root.unwrap();
+        assert_eq!(root.borrow().data, 7);
+        assert_eq!(BinaryTree::count(&root), values.len());
+        assert!(BinaryTree::is_valid_bst(&root));
+
+        let data: Vec<_> = BinaryTree::flatten_inorder(root)
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data, (1..=15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn into_iterator() {
+        let root = populate_balanced_binary_tree();
+        let expected: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        let tree = BinaryTree::with_root(root);
+        let mut names = Vec::new();
+        for node_ref in &tree {
+            names.push(node_ref.borrow().name.clone());
+        }
+        assert_eq!(names, expected);
+
+        // borrowing iteration leaves the tree usable
+        let names: Vec<_> = (&tree)
+            .into_iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, expected);
+
+        let names: Vec<_> = tree.into_iter().map(|n| n.borrow().name.clone()).collect();
+        assert_eq!(names, expected);
+
+        assert_eq!(BinaryTree::new().into_iter().count(), 0);
+    }
+
+    #[test]
+    fn into_iterator_unset_parents() {
+        let root = populate_balanced_binary_tree();
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().parent = Weak::new());
+
+        let tree = BinaryTree::with_root(root);
+        assert_eq!((&tree).into_iter().count(), NODES_COUNT);
+        assert_eq!(tree.into_iter().count(), NODES_COUNT);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let a = BinaryTree::with_root(populate_balanced_binary_search_tree());
+        let b = BinaryTree::with_root(populate_balanced_binary_search_tree());
+        assert!(a == b);
+        assert!(BinaryTree::new() == BinaryTree::new());
+        assert!(a != BinaryTree::new());
+
+        let c = BinaryTree::with_root(populate_balanced_binary_search_tree());
+        c.root.as_ref().unwrap().borrow_mut().data = 100;
+        assert!(a != c);
+    }
+
+    #[test]
+    fn clone() {
+        let tree = BinaryTree::with_root(populate_balanced_binary_search_tree());
+        let copy = tree.clone();
+        assert!(tree == copy);
+
+        let root = tree.root.as_ref().unwrap();
+        let copy_root = copy.root.as_ref().unwrap();
+        assert!(BinaryTree::is_valid_bst(copy_root));
+        assert_ne!(root.borrow().id, copy_root.borrow().id);
+
+        copy_root.borrow_mut().data = 100;
+        assert_eq!(root.borrow().data, 8);
+        assert!(BinaryTree::is_valid_bst(root));
+        assert!(tree != copy);
+
+        assert!(BinaryTree::new().clone().is_empty());
+    }
+
+    #[test]
+    // the hash only depends on `id`, which is not reachable through the `RefCell` links
+    #[allow(clippy::mutable_key_type)]
+    fn hash_by_id() {
+        let node = |name: &str| BinaryTreeNode {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            data: 1,
+            parent: Weak::new(),
+            left: None,
+            right: None,
+        };
+
+        let a = node("a");
+        let b = node("b");
+        let a_copy = BinaryTreeNode {
+            id: a.id,
+            ..node("a_copy")
+        };
+
+        let mut map = HashMap::<BinaryTreeNode, usize>::new();
+        map.insert(a, 1);
+        map.insert(b, 2);
+        assert_eq!(map.len(), 2);
+
+        // same id, so it replaces the entry of `a`
+        map.insert(a_copy, 3);
+        assert_eq!(map.len(), 2);
+        assert!(map.values().any(|v| *v == 3));
+        assert!(!map.values().any(|v| *v == 1));
+    }
+
+    #[test]
+    fn populate_right_spine_test() {
+        let root = populate_right_spine(5);
+        let nodes = BinaryTree::flatten_top_down(root);
+        assert_eq!(nodes.len(), 5);
+        for (index, node_ref) in nodes.iter().enumerate() {
+            let node = node_ref.borrow();
+            assert_eq!(node.name, format!("n{index}"));
+            assert!(node.left.is_none());
+        }
+    }
+
+    #[test]
+    fn count_recursive() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::count_recursive(&root), BinaryTree::count(&root));
+
+        let spine = populate_right_spine(10);
+        assert_eq!(BinaryTree::count_recursive(&spine), 10);
+        assert_eq!(
+            BinaryTree::count_recursive(&spine),
+            BinaryTree::count(&spine)
+        );
+
+        let node = BinaryTree::new_node();
+        assert_eq!(BinaryTree::count_recursive(&node), 1);
+        assert_eq!(BinaryTree::count_recursive(&node), BinaryTree::count(&node));
+    }
+
+    #[test]
+    fn is_same_node() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        assert!(BinaryTree::is_same_node(&root, &nodes[0]));
+        assert!(!BinaryTree::is_same_node(&root, &nodes[1]));
+
+        // same data and name is not enough
+        let copy = BinaryTree::clone_subtree(&root);
+        assert!(!BinaryTree::is_same_node(&root, &copy));
+    }
+
+    #[test]
+    fn builder() {
+        let tree = BinaryTreeBuilder::new()
+            .root(8, "n0")
+            .left(4, "n1")
+            .left(2, "n3")
+            .left(1, "n7")
+            .up()
+            .right(3, "n8")
+            .up()
+            .up()
+            .right(6, "n4")
+            .left(5, "n9")
+            .up()
+            .right(7, "n10")
+            .up()
+            .up()
+            .up()
+            .right(12, "n2")
+            .left(10, "n5")
+            .left(9, "n11")
+            .up()
+            .right(11, "n12")
+            .up()
+            .up()
+            .right(14, "n6")
+            .left(13, "n13")
+            .up()
+            .right(15, "n14")
+            .build()
+            .unwrap();
+
+        let expected = BinaryTree::with_root(populate_balanced_binary_search_tree());
+        assert!(tree == expected);
+
+        let root = tree.root.as_ref().unwrap();
+        assert!(BinaryTree::is_valid_bst(root));
+        let names: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        let expected_names: Vec<_> = (0..NODES_COUNT).map(|n| format!("n{}", n)).collect();
+        assert_eq!(names, expected_names);
+
+        // parents are assigned at build time
+        let leftmost = BinaryTree::leftmost(root).unwrap();
+        assert_eq!(
+            leftmost.borrow().parent.upgrade().unwrap().borrow().name,
+            "n3".to_string()
+        );
+
+        assert!(BinaryTreeBuilder::new().build().unwrap().is_empty());
+    }
+
+    #[test]
+    fn builder_missing_root() {
+        let built = BinaryTreeBuilder::new().left(1, "a").root(2, "b").build();
+        assert_eq!(built.err(), Some(TreeError::MissingRoot));
+
+        let built = BinaryTreeBuilder::new().up().right(1, "a").build();
+        assert_eq!(built.err(), Some(TreeError::MissingRoot));
+    }
+
+    #[test]
+    fn paths_of_length_k() {
+        let root = populate_balanced_binary_tree();
+        let names = |paths: &[Vec<BinaryTreeNodeRef>]| {
+            paths
+                .iter()
+                .map(|p| {
+                    p.iter()
+                        .map(|n| n.borrow().name.clone())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let paths = BinaryTree::paths_of_length_k(&root, 0);
+        assert_eq!(paths.len(), NODES_COUNT);
+        assert!(paths.iter().all(|p| p.len() == 1));
+
+        let paths = BinaryTree::paths_of_length_k(&root, 1);
+        assert_eq!(paths.len(), NODES_COUNT - 1);
+        assert_eq!(names(&paths[..2]), ["n0 n1", "n0 n2"]);
+
+        let paths = BinaryTree::paths_of_length_k(&root, 3);
+        assert_eq!(
+            names(&paths),
+            [
+                "n0 n1 n3 n7",
+                "n0 n1 n3 n8",
+                "n0 n1 n4 n9",
+                "n0 n1 n4 n10",
+                "n0 n2 n5 n11",
+                "n0 n2 n5 n12",
+                "n0 n2 n6 n13",
+                "n0 n2 n6 n14",
+            ]
+        );
+
+        assert!(BinaryTree::paths_of_length_k(&root, 4).is_empty());
+    }
+
+    #[test]
+    fn max_consecutive_sequence() {
+        let tree: BinaryTree = [5, 3, 6, 7, 8, 2, 4]
+            .iter()
+            .map(|v| (*v, format!("n{}", v)))
+            .collect();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::max_consecutive_sequence(&root), 4);
+
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::max_consecutive_sequence(&root), 2);
+
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::max_consecutive_sequence(&root), 2);
+
+        let root = populate_right_spine(5);
+        assert_eq!(BinaryTree::max_consecutive_sequence(&root), 5);
+
+        // 1 -> 2 -> 3 -> 4 below a root that breaks the run
+        let tree = BinaryTreeBuilder::new()
+            .root(9, "a")
+            .left(1, "b")
+            .right(2, "c")
+            .left(3, "d")
+            .right(4, "e")
+            .up()
+            .up()
+            .up()
+            .up()
+            .right(10, "f")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::max_consecutive_sequence(&root), 4);
+    }
+
+    #[test]
+    fn max_independent_set_sum() {
+        // leaves (64) and the level below the root (16)
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::max_independent_set_sum(&root), 80);
+
+        /*
+             3
+           /   \
+          2     3
+           \     \
+            3     1
+        */
+        let tree = BinaryTreeBuilder::new()
+            .root(3, "a")
+            .left(2, "b")
+            .right(3, "c")
+            .up()
+            .up()
+            .right(3, "d")
+            .right(1, "e")
+            .build()
+            .unwrap();
+        assert_eq!(
+            BinaryTree::max_independent_set_sum(tree.root.as_ref().unwrap()),
+            7
+        );
+
+        let node = BinaryTree::new_node_with_data(5, "n5");
+        assert_eq!(BinaryTree::max_independent_set_sum(&node), 5);
+    }
+
+    #[test]
+    fn flatten_levels() {
+        let root = populate_balanced_binary_tree();
+        let levels: Vec<Vec<_>> = BinaryTree::flatten_levels(&root)
+            .iter()
+            .map(|level| level.iter().map(|n| n.borrow().name.clone()).collect())
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                vec!["n0"],
+                vec!["n1", "n2"],
+                vec!["n3", "n4", "n5", "n6"],
+                vec!["n7", "n8", "n9", "n10", "n11", "n12", "n13", "n14"],
+            ]
+        );
+    }
+
+    #[test]
+    fn level_sums() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::level_sums(&root), [8, 16, 32, 64]);
+    }
+
+    #[test]
+    fn max_level_sum() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::max_level_sum(&root), 64);
+
+        let tree = BinaryTreeBuilder::new()
+            .root(1, "a")
+            .left(10, "b")
+            .left(1, "d")
+            .up()
+            .up()
+            .right(10, "c")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::level_sums(&root), [1, 20, 1]);
+        assert_eq!(BinaryTree::max_level_sum(&root), 20);
+    }
+
+    #[test]
+    fn two_sum_bst() {
+        let root = populate_balanced_binary_search_tree();
+
+        let (a, b) = BinaryTree::two_sum_bst(&root, 10).unwrap();
+        assert_eq!(a.borrow().data + b.borrow().data, 10);
+        assert!(a.borrow().data < b.borrow().data);
+
+        for target in 3..=29 {
+            let (a, b) = BinaryTree::two_sum_bst(&root, target).unwrap();
+            assert_eq!(a.borrow().data + b.borrow().data, target);
+            assert!(!BinaryTree::is_same_node(&a, &b));
+        }
+
+        assert!(BinaryTree::two_sum_bst(&root, 2).is_none());
+        assert!(BinaryTree::two_sum_bst(&root, 30).is_none());
+        assert!(BinaryTree::two_sum_bst(&root, 100).is_none());
+    }
+
+    #[test]
+    fn count_univalue_subtrees() {
+        // only the leaves
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::count_univalue_subtrees(&root), 8);
+
+        let root = populate_balanced_binary_tree();
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().data = 7);
+        assert_eq!(BinaryTree::count_univalue_subtrees(&root), NODES_COUNT);
+
+        // the right subtree of the root differs from the root
+        root.borrow().right.as_ref().unwrap().borrow_mut().data = 1;
+        assert_eq!(BinaryTree::count_univalue_subtrees(&root), NODES_COUNT - 2);
+    }
+
+    #[test]
+    fn are_cousins() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+
+        assert!(BinaryTree::are_cousins(&root, &nodes[7], &nodes[9]));
+        assert!(BinaryTree::are_cousins(&root, &nodes[7], &nodes[14]));
+        assert!(BinaryTree::are_cousins(&root, &nodes[3], &nodes[5]));
+
+        // siblings
+        assert!(!BinaryTree::are_cousins(&root, &nodes[7], &nodes[8]));
+        assert!(!BinaryTree::are_cousins(&root, &nodes[1], &nodes[2]));
+        // different depths
+        assert!(!BinaryTree::are_cousins(&root, &nodes[3], &nodes[9]));
+        // not in the tree
+        let other = BinaryTree::new_node();
+        assert!(!BinaryTree::are_cousins(&root, &nodes[7], &other));
+    }
+
+    #[test]
+    fn average_of_levels() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::average_of_levels(&root), [8.0, 8.0, 8.0, 8.0]);
+
+        let tree = BinaryTreeBuilder::new()
+            .root(3, "a")
+            .left(9, "b")
+            .up()
+            .right(20, "c")
+            .left(15, "d")
+            .up()
+            .right(7, "e")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::average_of_levels(&root), [3.0, 14.5, 11.0]);
+    }
+
+    #[test]
+    fn flatten_reverse_inorder() {
+        let root = populate_balanced_binary_tree();
+        let mut reversed: Vec<_> = BinaryTree::flatten_reverse_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        reversed.reverse();
+        let inorder: Vec<_> = BinaryTree::flatten_inorder(root)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(reversed, inorder);
+
+        let root = populate_balanced_binary_search_tree();
+        let data: Vec<_> = BinaryTree::flatten_reverse_inorder(root)
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data, (1..=15).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn count_in_range() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::count_in_range(&root, 1, 15), 15);
+        assert_eq!(BinaryTree::count_in_range(&root, 5, 10), 6);
+        assert_eq!(BinaryTree::count_in_range(&root, 8, 8), 1);
+        assert_eq!(BinaryTree::count_in_range(&root, 0, 3), 3);
+        assert_eq!(BinaryTree::count_in_range(&root, 14, 100), 2);
+        assert_eq!(BinaryTree::count_in_range(&root, 16, 100), 0);
+        assert_eq!(BinaryTree::count_in_range(&root, 10, 5), 0);
+    }
+
+    #[test]
+    fn largest_bst_subtree() {
+        let root = populate_balanced_binary_search_tree();
+        let largest = BinaryTree::largest_bst_subtree(&root).unwrap();
+        assert!(BinaryTree::is_same_node(&largest, &root));
+
+        // break the root and its left subtree, the right subtree stays valid
+        root.borrow_mut().data = 100;
+        BinaryTree::leftmost(&root).unwrap().borrow_mut().data = 50;
+        let largest = BinaryTree::largest_bst_subtree(&root).unwrap();
+        assert_eq!(largest.borrow().data, 12);
+        assert_eq!(BinaryTree::count(&largest), 7);
+
+        let root = populate_balanced_binary_tree();
+        let largest = BinaryTree::largest_bst_subtree(&root).unwrap();
+        assert_eq!(BinaryTree::count(&largest), 1);
+    }
+
+    #[test]
+    fn complete_tree_count() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(
+            BinaryTree::complete_tree_count(&root),
+            BinaryTree::count(&root)
+        );
+
+        let node = BinaryTree::new_node();
+        assert_eq!(BinaryTree::complete_tree_count(&node), 1);
+
+        // drop the last leaves, the tree stays complete
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        nodes[6].borrow_mut().right = None;
+        assert_eq!(BinaryTree::complete_tree_count(&root), 14);
+        nodes[6].borrow_mut().left = None;
+        nodes[5].borrow_mut().right = None;
+        assert_eq!(BinaryTree::complete_tree_count(&root), 12);
+        assert_eq!(
+            BinaryTree::complete_tree_count(&root),
+            BinaryTree::count(&root)
+        );
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_balanced_binary_search_tree(),
+        ] {
+            let csv = BinaryTree::to_csv(&root);
+            assert_eq!(csv.lines().count(), NODES_COUNT + 1);
+
+            let tree = BinaryTree::from_csv(&csv).unwrap();
+            let restored = tree.root.as_ref().unwrap();
+            assert!(BinaryTree::same_tree(&root, restored));
+
+            let ids = |root: &BinaryTreeNodeRef| {
+                BinaryTree::flatten_top_down(root.clone())
+                    .iter()
+                    .map(|n| (n.borrow().id, n.borrow().name.clone()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(ids(&root), ids(restored));
+            assert_eq!(BinaryTree::to_csv(restored), csv);
+        }
+    }
+
+    #[test]
+    fn csv_round_trip_right_spine() {
+        let root = populate_right_spine(3);
+        let tree = BinaryTree::from_csv(&BinaryTree::to_csv(&root)).unwrap();
+        let restored = tree.root.as_ref().unwrap();
+        assert!(BinaryTree::same_tree(&root, restored));
+        assert!(restored.borrow().left.is_none());
+    }
+
+    #[test]
+    fn csv_unset_parents() {
+        let root = populate_balanced_binary_tree();
+        let csv = BinaryTree::to_csv(&root);
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().parent = Weak::new());
+        assert_eq!(BinaryTree::to_csv(&root), csv);
+
+        let tree = BinaryTree::from_csv(&csv).unwrap();
+        assert!(BinaryTree::same_tree(&root, tree.root.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn csv_quoted_name() {
+        let root = BinaryTree::new_node_with_data(1, "a, \"b\"");
+        let csv = BinaryTree::to_csv(&root);
+        let tree = BinaryTree::from_csv(&csv).unwrap();
+        assert_eq!(tree.root.unwrap().borrow().name, "a, \"b\"".to_string());
+    }
+
+    #[test]
+    fn csv_multiline_name() {
+        let root = BinaryTree::new_node_with_data(1, "a\nb");
+        let child = BinaryTree::new_node_with_data(2, "c\r\n\"d\"");
+        root.borrow_mut().right = Some(child.clone());
+        let csv = BinaryTree::to_csv(&root);
+
+        let tree = BinaryTree::from_csv(&csv).unwrap();
+        let restored = tree.root.as_ref().unwrap();
+        assert!(BinaryTree::same_tree(&root, restored));
+        assert_eq!(restored.borrow().name, "a\nb");
+        let restored_child = restored.borrow().right.clone().unwrap();
+        assert_eq!(restored_child.borrow().name, "c\r\n\"d\"");
+
+        // errors point at the line the record starts on
+        let csv = csv.replace(",2,", ",x,");
+        let error = BinaryTree::from_csv(&csv).err().unwrap();
+        assert!(error.starts_with("line 4:"));
+    }
+
+    #[test]
+    fn from_csv_errors() {
+        const HEADER: &str = "id,name,data,parent_id,side\n";
+        assert!(BinaryTree::from_csv("id,name\n").is_err());
+        assert!(BinaryTree::from_csv("id,name,data,parent_id\n").is_err());
+        assert!(BinaryTree::from_csv(&format!("{}not-an-id,n0,0,,\n", HEADER)).is_err());
+
+        let id = Uuid::new_v4();
+        let csv = format!("{}{},n0,x,,\n", HEADER, id);
+        assert!(BinaryTree::from_csv(&csv).is_err());
+
+        let csv = format!("{}{},n0,0,{},left\n", HEADER, id, Uuid::new_v4());
+        assert!(BinaryTree::from_csv(&csv).is_err());
+
+        let csv = format!("{}{},n0,0,,left\n", HEADER, id);
+        assert!(BinaryTree::from_csv(&csv).is_err());
+
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let csv = format!("{}{},n0,0,,\n{},n1,1,{},up\n", HEADER, id, a, id);
+        assert!(BinaryTree::from_csv(&csv).is_err());
+
+        let csv = format!(
+            "{}{},n0,0,,\n{},n1,1,{},right\n{},n2,2,{},right\n",
+            HEADER, id, a, id, b, id
+        );
+        assert!(BinaryTree::from_csv(&csv).is_err());
+
+        let tree = BinaryTree::from_csv(HEADER).unwrap();
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn xml_round_trip() {
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_balanced_binary_search_tree(),
+            populate_right_spine(5),
+        ] {
+            let xml = BinaryTree::to_xml(&root);
+            let tree = BinaryTree::from_xml(&xml).unwrap();
+            let restored = tree.root.as_ref().unwrap();
+            assert!(BinaryTree::same_tree(&root, restored));
+            assert_eq!(BinaryTree::to_xml(restored), xml);
+
+            let leftmost = BinaryTree::leftmost(restored).unwrap_or(restored.clone());
+            assert_eq!(
+                BinaryTree::get_root(&leftmost).borrow().id,
+                root.borrow().id
+            );
+        }
+    }
+
+    #[test]
+    fn xml_escaped_name() {
+        let root = BinaryTree::new_node_with_data(1, "<a & \"b\">");
+        let xml = BinaryTree::to_xml(&root);
+        assert!(!xml.contains("<a &"));
+        let tree = BinaryTree::from_xml(&xml).unwrap();
+        assert_eq!(tree.root.unwrap().borrow().name, "<a & \"b\">".to_string());
+    }
+
+    #[test]
+    fn from_xml_errors() {
+        assert!(BinaryTree::from_xml("").is_err());
+        assert!(BinaryTree::from_xml("<node name=\"n0\" data=\"0\"></node>").is_err());
+
+        let id = Uuid::new_v4();
+        let xml = format!("<node id=\"{}\" name=\"n0\" data=\"x\"></node>", id);
+        assert!(BinaryTree::from_xml(&xml).is_err());
+
+        let xml = format!("<node id=\"{}\" name=\"n0\" data=\"0\"><left></node>", id);
+        assert!(BinaryTree::from_xml(&xml).is_err());
+
+        let xml = format!("<node id=\"{}\" name=\"n0\" data=\"0\">", id);
+        assert!(BinaryTree::from_xml(&xml).is_err());
+    }
+
+    #[test]
+    fn print_level_by_level() {
+        let root = populate_balanced_binary_tree();
+        let mut out = Vec::<u8>::new();
+        BinaryTree::print_level_by_level(&root, &mut out).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(
+            lines,
+            ["n0", "n1 n2", "n3 n4 n5 n6", "n7 n8 n9 n10 n11 n12 n13 n14"]
+        );
+    }
+
+    #[test]
+    fn flatten_preorder() {
+        let expected = [
+            "n0", "n1", "n3", "n7", "n8", "n4", "n9", "n10", "n2", "n5", "n11", "n12", "n6", "n13",
+            "n14",
+        ];
+
+        let root = populate_balanced_binary_tree();
+        let names: Vec<_> = BinaryTree::flatten_preorder(root)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn flatten_preorder_morris() {
+        for root in [populate_balanced_binary_tree(), populate_right_spine(5)] {
+            let original = BinaryTree::clone_subtree(&root);
+            let expected: Vec<_> = BinaryTree::flatten_preorder(root.clone())
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect();
+
+            let names: Vec<_> = BinaryTree::flatten_preorder_morris(root.clone())
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect();
+            assert_eq!(names, expected);
+
+            // all temporary threads are removed
+            assert!(BinaryTree::same_tree(&root, &original));
+        }
+    }
+
+    #[test]
+    fn minimum_depth() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::minimum_depth(&root), 3);
+
+        let root = populate_right_spine(5);
+        assert_eq!(BinaryTree::minimum_depth(&root), 4);
+
+        let node = BinaryTree::new_node();
+        assert_eq!(BinaryTree::minimum_depth(&node), 0);
+
+        let tree = BinaryTreeBuilder::new()
+            .root(1, "a")
+            .left(2, "b")
+            .up()
+            .right(3, "c")
+            .right(4, "d")
+            .build()
+            .unwrap();
+        assert_eq!(BinaryTree::minimum_depth(tree.root.as_ref().unwrap()), 1);
+    }
+
+    #[test]
+    fn maximum_depth() {
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_right_spine(5),
+            BinaryTree::new_node(),
+        ] {
+            let height = BinaryTree::height(&root);
+            assert_eq!(BinaryTree::maximum_depth(&root), height);
+            assert_eq!(BinaryTree::with_root(root).depth(), height);
+        }
+        assert_eq!(BinaryTree::new().depth(), 0);
+
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::with_root(root).depth(), 3);
+    }
+
+    #[test]
+    fn fill_next_right() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let mut next_map = HashMap::new();
+        BinaryTree::fill_next_right(&root, &mut next_map);
+        assert_eq!(next_map.len(), NODES_COUNT);
+
+        // level 3: n7 -> n8 -> ... -> n14
+        for index in 7..NODES_COUNT {
+            let next = next_map[&nodes[index].borrow().id];
+            if index == NODES_COUNT - 1 {
+                assert_eq!(next, None);
+            } else {
+                assert_eq!(next, Some(nodes[index + 1].borrow().id));
+            }
+        }
+
+        // rightmost nodes of each level
+        for index in [0, 2, 6, 14] {
+            assert_eq!(next_map[&nodes[index].borrow().id], None);
+        }
+        assert_eq!(next_map[&nodes[4].borrow().id], Some(nodes[5].borrow().id));
+    }
+
+    #[test]
+    fn flatten_inorder_morris() {
+        let root = populate_balanced_binary_tree();
+        let original = BinaryTree::clone_subtree(&root);
+        let expected: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        let names: Vec<_> = BinaryTree::flatten_inorder_morris(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, expected);
+        assert!(BinaryTree::same_tree(&root, &original));
+    }
+
+    #[test]
+    fn recover_bst() {
+        let swap = |a: usize, b: usize| {
+            let root = populate_balanced_binary_search_tree();
+            let nodes = BinaryTree::flatten_top_down(root.clone());
+            let data = nodes[a].borrow().data;
+            nodes[a].borrow_mut().data = nodes[b].borrow().data;
+            nodes[b].borrow_mut().data = data;
+            root
+        };
+
+        // adjacent in inorder (7, 8), far apart (1, 15), parent and child (4, 2)
+        for (a, b) in [(10, 0), (7, 14), (1, 3)] {
+            let root = swap(a, b);
+            assert!(!BinaryTree::is_valid_bst(&root));
+            BinaryTree::recover_bst(&root);
+            assert!(BinaryTree::is_valid_bst(&root));
+            assert!(BinaryTree::same_tree(
+                &root,
+                &populate_balanced_binary_search_tree()
+            ));
+        }
+    }
+
+    #[test]
+    fn right_side_view() {
+        let root = populate_balanced_binary_tree();
+        let names: Vec<_> = BinaryTree::right_side_view(&root)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, ["n0", "n2", "n6", "n14"]);
+    }
+
+    #[test]
+    fn right_view_dfs() {
+        let root = populate_balanced_binary_tree();
+        let names = |nodes: Vec<BinaryTreeNodeRef>| {
+            nodes
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(BinaryTree::right_view_dfs(&root)),
+            names(BinaryTree::right_side_view(&root))
+        );
+
+        // a deeper left subtree shows up in the view
+        let tree = BinaryTreeBuilder::new()
+            .root(1, "a")
+            .left(2, "b")
+            .right(4, "d")
+            .up()
+            .up()
+            .right(3, "c")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(names(BinaryTree::right_view_dfs(&root)), ["a", "c", "d"]);
+        assert_eq!(
+            names(BinaryTree::right_view_dfs(&root)),
+            names(BinaryTree::right_side_view(&root))
+        );
+    }
+
+    #[test]
+    fn to_dll() {
+        let root = populate_balanced_binary_search_tree();
+        let expected: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+
+        let (head, tail) = BinaryTree::to_dll(&root);
+        assert_eq!(head.borrow().data, 1);
+        assert_eq!(tail.borrow().data, 15);
+
+        let walk = |start: &BinaryTreeNodeRef, forward: bool| {
+            let mut values = Vec::new();
+            let mut current = start.clone();
+            for _ in 0..NODES_COUNT {
+                values.push(current.borrow().data);
+                let next = if forward {
+                    current.borrow().right.clone()
+                } else {
+                    current.borrow().left.clone()
+                };
+                current = next.unwrap();
+            }
+            // back at the start of the circle
+            assert!(BinaryTree::is_same_node(&current, start));
+            values
+        };
+
+        assert_eq!(walk(&head, true), expected);
+        let mut backward = walk(&tail, false);
+        backward.reverse();
+        assert_eq!(backward, expected);
+
+        let mut current = Some(head);
+        while let Some(node_ref) = current {
+            if node_ref.borrow_mut().left.take().is_none() {
+                break;
+            }
+            current = node_ref.borrow().right.clone();
+        }
+    }
+
+    #[test]
+    fn flatten_bottom_up() {
+        let root = populate_balanced_binary_tree();
+        let levels: Vec<Vec<_>> = BinaryTree::flatten_bottom_up(&root)
+            .iter()
+            .map(|level| level.iter().map(|n| n.borrow().name.clone()).collect())
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                vec!["n7", "n8", "n9", "n10", "n11", "n12", "n13", "n14"],
+                vec!["n3", "n4", "n5", "n6"],
+                vec!["n1", "n2"],
+                vec!["n0"],
+            ]
+        );
+    }
+
+    #[test]
+    fn minimum_height_roots() {
+        assert_eq!(
+            BinaryTree::minimum_height_roots(0, &[]),
+            Vec::<usize>::new()
+        );
+        assert_eq!(BinaryTree::minimum_height_roots(1, &[]), [0]);
+        assert_eq!(BinaryTree::minimum_height_roots(2, &[(0, 1)]), [0, 1]);
+        assert_eq!(
+            BinaryTree::minimum_height_roots(4, &[(1, 0), (1, 2), (1, 3)]),
+            [1]
+        );
+        assert_eq!(
+            BinaryTree::minimum_height_roots(6, &[(3, 0), (3, 1), (3, 2), (3, 4), (5, 4)]),
+            [3, 4]
+        );
+
+        // a path of 5 nodes has a single center
+        assert_eq!(
+            BinaryTree::minimum_height_roots(5, &[(0, 1), (1, 2), (2, 3), (3, 4)]),
+            [2]
+        );
+    }
+
+    #[test]
+    fn minimum_height_roots_not_a_tree() {
+        let roots = |n, edges: &[(usize, usize)]| BinaryTree::minimum_height_roots(n, edges);
+        // too few or too many edges
+        assert!(roots(3, &[]).is_empty());
+        assert!(roots(2, &[]).is_empty());
+        assert!(roots(1, &[(0, 0)]).is_empty());
+        assert!(roots(3, &[(0, 1), (1, 2), (2, 0)]).is_empty());
+        // a cycle plus an isolated node, or two components, with n - 1 edges
+        assert!(roots(4, &[(0, 1), (1, 2), (2, 0)]).is_empty());
+        assert!(roots(5, &[(0, 1), (1, 2), (2, 0), (3, 4)]).is_empty());
+        assert!(roots(3, &[(0, 1), (0, 1)]).is_empty());
+        // a self-loop counts as an edge but connects nothing
+        assert!(roots(4, &[(0, 0), (1, 2), (2, 3)]).is_empty());
+        assert!(roots(2, &[(0, 0)]).is_empty());
+        // out of range nodes
+        assert!(roots(3, &[(0, 1), (1, 3)]).is_empty());
+        assert!(roots(2, &[(0, 5)]).is_empty());
+    }
+
+    #[test]
+    fn unique_bst_count() {
+        let expected = [1, 1, 2, 5, 14, 42, 132];
+        for (n, count) in expected.iter().enumerate() {
+            assert_eq!(BinaryTree::unique_bst_count(n as u32), *count);
+        }
+        assert_eq!(BinaryTree::unique_bst_count(36), 11959798385860453492);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows u64 for n > 36")]
+    fn unique_bst_count_overflow() {
+        BinaryTree::unique_bst_count(37);
+    }
+
+    #[test]
+    fn generate_all_bsts() {
+        for n in 1..=4 {
+            let trees = BinaryTree::generate_all_bsts(n);
+            assert_eq!(trees.len() as u64, BinaryTree::unique_bst_count(n));
+
+            for (index, tree) in trees.iter().enumerate() {
+                let root = tree.root.as_ref().unwrap();
+                assert!(BinaryTree::is_valid_bst(root));
+                assert_eq!(BinaryTree::count(root), n as usize);
+                assert!(trees[index + 1..].iter().all(|other| other != tree));
+            }
+        }
+
+        let trees = BinaryTree::generate_all_bsts(0);
+        assert_eq!(trees.len(), 1);
+        assert!(trees[0].is_empty());
+    }
+
+    #[test]
+    fn to_table() {
+        let expected = "\
+name | data | parent | left | right
+-----+------+--------+------+------
+n0   | 0    | -      | n1   | n2
+n1   | 1    | n0     | n3   | n4
+n2   | 2    | n0     | n5   | n6
+n3   | 3    | n1     | n7   | n8
+n4   | 4    | n1     | n9   | n10
+n5   | 5    | n2     | n11  | n12
+n6   | 6    | n2     | n13  | n14
+n7   | 7    | n3     | -    | -
+n8   | 8    | n3     | -    | -
+n9   | 9    | n4     | -    | -
+n10  | 10   | n4     | -    | -
+n11  | 11   | n5     | -    | -
+n12  | 12   | n5     | -    | -
+n13  | 13   | n6     | -    | -
+n14  | 14   | n6     | -    | -
+";
+
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::to_table(&root), expected);
+    }
+
+    #[test]
+    fn assign_incremental_data() {
+        let root = populate_balanced_binary_search_tree();
+
+        BinaryTree::assign_incremental_data(&root, 0, 1);
+        let data: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data, (0..NODES_COUNT as u32).collect::<Vec<_>>());
+
+        BinaryTree::assign_incremental_data(&root, 10, 5);
+        let data: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data[..4], [10, 15, 20, 25]);
+        assert_eq!(data[NODES_COUNT - 1], 80);
+
+        BinaryTree::assign_incremental_data(&root, u32::MAX - 1, 1);
+        let data: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data[..4], [u32::MAX - 1, u32::MAX, 0, 1]);
+
+        BinaryTree::assign_incremental_data(&root, 0, u32::MAX);
+        assert_eq!(
+            root.borrow().right.as_ref().unwrap().borrow().data,
+            u32::MAX - 1
+        );
+    }
+
+    #[test]
+    fn count_all_paths() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::count_all_paths(&root), 105);
+        assert_eq!(BinaryTree::count_all_paths(&BinaryTree::new_node()), 0);
+        assert_eq!(BinaryTree::count_all_paths(&populate_right_spine(4)), 6);
+    }
+
+    #[test]
+    fn count_paths_passing_through() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        assert_eq!(
+            BinaryTree::count_paths_passing_through(&root, &nodes[0]),
+            63
+        );
+        assert_eq!(
+            BinaryTree::count_paths_passing_through(&root, &nodes[1]),
+            71
+        );
+        assert_eq!(
+            BinaryTree::count_paths_passing_through(&root, &nodes[3]),
+            39
+        );
+        assert_eq!(
+            BinaryTree::count_paths_passing_through(&root, &nodes[7]),
+            14
+        );
+
+        // on a path of 0..4 the node 1 is an inner node of 1 * 3 paths
+        let root = populate_right_spine(5);
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        assert_eq!(BinaryTree::count_paths_passing_through(&root, &nodes[1]), 7);
+        assert_eq!(BinaryTree::count_paths_passing_through(&root, &nodes[2]), 8);
+
+        // a node from another tree, or the root of a larger one
+        let other = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::count_paths_passing_through(&root, &other), 0);
+        assert_eq!(BinaryTree::count_paths_passing_through(&nodes[3], &root), 0);
+    }
+
+    #[test]
+    fn trim_bst() {
+        let root = populate_balanced_binary_search_tree();
+
+        let trimmed = BinaryTree::trim_bst(&root, 5, 10).unwrap();
+        assert!(BinaryTree::is_valid_bst(&trimmed));
+        let data: Vec<_> = BinaryTree::flatten_inorder(trimmed)
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data, [5, 6, 7, 8, 9, 10]);
+
+        // the original tree is left untouched
+        assert_eq!(BinaryTree::count(&root), NODES_COUNT);
+        assert!(BinaryTree::same_tree(
+            &root,
+            &populate_balanced_binary_search_tree()
+        ));
+
+        let trimmed = BinaryTree::trim_bst(&root, 0, 100).unwrap();
+        assert!(BinaryTree::same_tree(&root, &trimmed));
+
+        assert!(BinaryTree::trim_bst(&root, 16, 100).is_none());
+    }
+
+    #[test]
+    fn insert_complete() {
+        let root = populate_balanced_binary_tree();
+        let node_ref = BinaryTree::insert_complete(&root, 15, "n15");
+        assert_eq!(BinaryTree::count(&root), NODES_COUNT + 1);
+        assert_eq!(
+            node_ref.borrow().parent.upgrade().unwrap().borrow().name,
+            "n7".to_string()
+        );
+
+        BinaryTree::insert_complete(&root, 16, "n16");
+        let names: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        let expected: Vec<_> = (0..NODES_COUNT + 2).map(|n| format!("n{}", n)).collect();
+        assert_eq!(names, expected);
+        assert_eq!(BinaryTree::complete_tree_count(&root), NODES_COUNT + 2);
+    }
+
+    #[test]
+    fn delete_deepest_rightmost() {
+        let root = populate_balanced_binary_tree();
+        for count in (1..NODES_COUNT).rev() {
+            let removed = BinaryTree::delete_deepest_rightmost(&root).unwrap();
+            assert_eq!(removed.borrow().name, format!("n{}", count));
+            assert!(removed.borrow().parent.upgrade().is_none());
+            assert_eq!(BinaryTree::count(&root), count);
+            assert_eq!(BinaryTree::complete_tree_count(&root), count);
+        }
+        assert!(BinaryTree::delete_deepest_rightmost(&root).is_none());
+        assert_eq!(BinaryTree::count(&root), 1);
+    }
+
+    #[test]
+    fn lca_and_distance() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let check = |a: usize, b: usize, lca: &str, distance: usize| {
+            let (node, d) = BinaryTree::lca_and_distance(&root, &nodes[a], &nodes[b]).unwrap();
+            assert_eq!(node.borrow().name, lca.to_string());
+            assert_eq!(d, distance);
+        };
+
+        check(7, 8, "n3", 2);
+        check(7, 9, "n1", 4);
+        check(7, 14, "n0", 6);
+        check(3, 7, "n3", 1);
+        check(0, 10, "n0", 3);
+        check(5, 5, "n5", 0);
+        check(12, 1, "n0", 4);
+
+        let other = BinaryTree::new_node();
+        assert!(BinaryTree::lca_and_distance(&root, &nodes[7], &other).is_none());
+    }
+
+    #[test]
+    fn has_path_sum() {
+        let root = populate_balanced_binary_search_tree();
+        for target in [15, 17, 23, 25, 39, 41, 47, 49] {
+            assert!(BinaryTree::has_path_sum(&root, target));
+        }
+        assert!(!BinaryTree::has_path_sum(&root, 16));
+        // 8 + 4 + 2 is not a root-to-leaf path
+        assert!(!BinaryTree::has_path_sum(&root, 14));
+    }
+
+    #[test]
+    fn paths_with_sum() {
+        let data = |paths: Vec<Vec<BinaryTreeNodeRef>>| {
+            paths
+                .iter()
+                .map(|p| p.iter().map(|n| n.borrow().data).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(data(BinaryTree::paths_with_sum(&root, 15)), [[8, 4, 2, 1]]);
+        assert_eq!(data(BinaryTree::paths_with_sum(&root, 25)), [[8, 4, 6, 7]]);
+        assert_eq!(
+            data(BinaryTree::paths_with_sum(&root, 49)),
+            [[8, 12, 14, 15]]
+        );
+        assert!(BinaryTree::paths_with_sum(&root, 14).is_empty());
+
+        let tree = BinaryTreeBuilder::new()
+            .root(5, "a")
+            .left(4, "b")
+            .left(11, "c")
+            .left(2, "d")
+            .up()
+            .right(7, "e")
+            .up()
+            .up()
+            .up()
+            .right(8, "f")
+            .left(13, "g")
+            .up()
+            .right(4, "h")
+            .left(5, "i")
+            .up()
+            .right(1, "j")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(
+            data(BinaryTree::paths_with_sum(&root, 22)),
+            [vec![5, 4, 11, 2], vec![5, 8, 4, 5]]
+        );
+        assert_eq!(data(BinaryTree::paths_with_sum(&root, 27)), [[5, 4, 11, 7]]);
+    }
+
+    #[test]
+    fn to_sorted_array() {
+        let root = populate_balanced_binary_search_tree();
+        let arr = BinaryTree::to_sorted_array(&root);
+        assert_eq!(arr.len(), NODES_COUNT);
+        assert_eq!(arr[0], (1, "n7".to_string()));
+        assert_eq!(arr[7], (8, "n0".to_string()));
+        assert!(arr.windows(2).all(|v| v[0].0 < v[1].0));
+    }
+
+    #[test]
+    fn from_sorted_array() {
+        let values = [5, 3, 6, 7, 8, 2, 4, 1];
+        let tree: BinaryTree = values.iter().map(|v| (*v, format!("n{}", v))).collect();
+        let root = tree.root.unwrap();
+        assert!(!BinaryTree::is_balanced(&root));
+
+        let arr = BinaryTree::to_sorted_array(&root);
+        let rebuilt = BinaryTree::from_sorted_array(&arr);
+        let rebuilt_root = rebuilt.root.as_ref().unwrap();
+        assert!(BinaryTree::is_valid_bst(rebuilt_root));
+        assert!(BinaryTree::is_balanced(rebuilt_root));
+
+        // same data, different shape
+        assert_eq!(BinaryTree::to_sorted_array(rebuilt_root), arr);
+        assert!(!BinaryTree::same_tree(&root, rebuilt_root));
+
+        assert!(BinaryTree::from_sorted_array(&[]).is_empty());
+    }
+
+    #[test]
+    fn from_cartesian() {
+        let values = [
+            (9, "a"),
+            (3, "b"),
+            (7, "c"),
+            (1, "d"),
+            (8, "e"),
+            (12, "f"),
+            (10, "g"),
+            (20, "h"),
+            (15, "i"),
+            (18, "j"),
+            (5, "k"),
+        ];
+        let tree = BinaryTree::from_cartesian(&values);
+        let root = tree.root.unwrap();
+        assert_eq!(root.borrow().name, "d".to_string());
+        assert_eq!(BinaryTree::count(&root), values.len());
+
+        let inorder: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| (n.borrow().data, n.borrow().name.clone()))
+            .collect();
+        let expected: Vec<_> = values.iter().map(|(d, n)| (*d, n.to_string())).collect();
+        assert_eq!(inorder, expected);
+
+        // heap order: no child is smaller than its parent
+        for node_ref in BinaryTree::flatten_top_down(root) {
+            let node = node_ref.borrow();
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(child.borrow().data >= node.data);
+            }
+        }
+
+        assert!(BinaryTree::from_cartesian(&[]).is_empty());
+    }
+
+    #[test]
+    fn flatten_inorder_stack() {
+        let root = populate_balanced_binary_tree();
+        let inorder: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().parent = Weak::new());
+        let names: Vec<_> = BinaryTree::flatten_inorder_stack(root)
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, inorder);
+
+        let root = populate_right_spine(4);
+        assert_eq!(BinaryTree::flatten_inorder_stack(root).len(), 4);
+    }
+
+    #[test]
+    fn get_root_safe() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        for node in &nodes {
+            let found = BinaryTree::get_root_safe(node).unwrap();
+            assert!(BinaryTree::is_same_node(&found, &root));
+        }
+
+        // n0 -> n1 -> n3 -> n0
+        root.borrow_mut().parent = Rc::downgrade(&nodes[3]);
+        assert_eq!(
+            BinaryTree::get_root_safe(&nodes[7]).unwrap_err(),
+            TreeError::Cycle
+        );
+
+        // a node being its own parent
+        let node = BinaryTree::new_node();
+        node.borrow_mut().parent = Rc::downgrade(&node);
+        assert_eq!(
+            BinaryTree::get_root_safe(&node).unwrap_err(),
+            TreeError::Cycle
+        );
+    }
+
+    #[test]
+    fn validate_parents() {
+        let root = populate_balanced_binary_tree();
+        BinaryTree::assign_parents(&root);
+        assert_eq!(BinaryTree::validate_parents(&root), Ok(()));
+
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        // n4 points to n2 instead of n1, n9 loses its parent
+        nodes[4].borrow_mut().parent = Rc::downgrade(&nodes[2]);
+        nodes[9].borrow_mut().parent = Weak::new();
+        let expected = vec![nodes[4].borrow().id, nodes[9].borrow().id];
+        assert_eq!(BinaryTree::validate_parents(&root), Err(expected));
+
+        assert_eq!(
+            BinaryTree::validate_parents(&BinaryTree::new_node()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn validate_reachable() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::validate_reachable(&root), Ok(()));
+
+        // n6 points up to a detached node, which has a child of its own
+        let detached = BinaryTree::new_node();
+        let detached_child = BinaryTree::new_node();
+        detached.borrow_mut().left = Some(detached_child.clone());
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        nodes[6].borrow_mut().parent = Rc::downgrade(&detached);
+
+        let expected = vec![detached.borrow().id, detached_child.borrow().id];
+        assert_eq!(BinaryTree::validate_reachable(&root), Err(expected));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn count_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_right_spine(100),
+            BinaryTree::new_node(),
+        ] {
+            let count = runtime.block_on(BinaryTree::count_async(root.clone()));
+            assert_eq!(count, BinaryTree::count(&root));
+        }
+    }
+
+    #[test]
+    fn inorder_channel() {
+        let root = populate_balanced_binary_tree();
+        let inorder = BinaryTree::flatten_inorder(root.clone());
+        let receiver = BinaryTree::inorder_channel(root.clone());
+
+        for node in inorder.iter().take(BinaryTree::count(&root)) {
+            let value = receiver.recv().unwrap();
+            let node = node.borrow();
+            assert_eq!(value.id, node.id);
+            assert_eq!(value.name, node.name);
+            assert_eq!(value.data, node.data);
+        }
+        // the sender is dropped once the traversal is done
+        assert!(receiver.recv().is_err());
+
+        // stopping early does not block the background thread
+        let receiver = BinaryTree::inorder_channel(root);
+        assert_eq!(receiver.recv().unwrap().name, "n7");
+        drop(receiver);
+    }
+
+    #[test]
+    fn depth_map() {
+        let root = populate_balanced_binary_tree();
+        let depths = BinaryTree::depth_map(&root);
+        assert_eq!(depths.len(), NODES_COUNT);
+
+        // n0 at depth 0, n1..n2 at 1, n3..n6 at 2, n7..n14 at 3
+        for (index, node) in BinaryTree::flatten_top_down(root).iter().enumerate() {
+            let expected = (index + 1).ilog2() as usize;
+            assert_eq!(depths[&node.borrow().id], expected);
+        }
+    }
+
+    #[test]
+    fn subtree_size_map() {
+        let root = populate_balanced_binary_tree();
+        let sizes = BinaryTree::subtree_size_map(&root);
+        assert_eq!(sizes.len(), NODES_COUNT);
+        assert_eq!(sizes[&root.borrow().id], BinaryTree::count(&root));
+
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let size = |index: usize| sizes[&nodes[index].borrow().id];
+        assert_eq!(size(0), 15);
+        assert!((1..=2).all(|i| size(i) == 7));
+        assert!((3..=6).all(|i| size(i) == 3));
+        assert!((7..=14).all(|i| size(i) == 1));
+    }
+
+    #[test]
+    fn hld() {
+        let root = populate_balanced_binary_search_tree();
+        let hld = BinaryTree::hld(&root);
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+
+        // ties go to the left child: 8 -> 4 -> 2 -> 1 is the first chain
+        let root_id = root.borrow().id;
+        for index in [0, 1, 3, 7] {
+            let id = nodes[index].borrow().id;
+            assert_eq!(hld.chain_head(id), Some(root_id));
+            assert_eq!(hld.position(id), Some((index + 1).ilog2() as usize));
+        }
+        let id = nodes[2].borrow().id;
+        assert_eq!(hld.chain_head(id), Some(id));
+
+        let mut positions: Vec<_> = nodes
+            .iter()
+            .map(|n| hld.position(n.borrow().id).unwrap())
+            .collect();
+        positions.sort();
+        assert_eq!(positions, (0..NODES_COUNT).collect::<Vec<_>>());
+
+        let mut data = vec![0; NODES_COUNT];
+        for node in &nodes {
+            let node = node.borrow();
+            data[hld.position(node.id).unwrap()] = node.data;
+        }
+        assert_eq!(hld.path_query(&root, &root), None);
+        let hld = hld.with_data(&data).unwrap();
+
+        // every pair against the maximum along the explicit path
+        for a in &nodes {
+            for b in &nodes {
+                let path_a = BinaryTree::path_from_root(&root, a).unwrap();
+                let path_b = BinaryTree::path_from_root(&root, b).unwrap();
+                let common = path_a
+                    .iter()
+                    .zip(&path_b)
+                    .take_while(|(x, y)| BinaryTree::is_same_node(x, y))
+                    .count();
+                let expected = path_a[common - 1..]
+                    .iter()
+                    .chain(&path_b[common..])
+                    .map(|n| n.borrow().data)
+                    .max()
+                    .unwrap();
+                assert_eq!(hld.path_query(a, b), Some(expected));
+            }
+        }
+
+        let other = BinaryTree::new_node_with_data(100, "other");
+        assert_eq!(hld.path_query(&root, &other), None);
+    }
+
+    #[test]
+    fn hld_data_length() {
+        let root = populate_balanced_binary_tree();
+        let error = BinaryTree::hld(&root)
+            .with_data(&[0; NODES_COUNT - 1])
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TreeError::DataLength {
+                expected: NODES_COUNT,
+                found: NODES_COUNT - 1
+            }
+        );
+    }
+
+    #[test]
+    fn euler_tour() {
+        let root = populate_balanced_binary_tree();
+        let (tour, first) = BinaryTree::euler_tour(&root);
+        assert_eq!(tour.len(), 2 * NODES_COUNT - 1);
+        assert_eq!(first.len(), NODES_COUNT);
+
+        let names: Vec<_> = tour[..8].iter().map(|n| n.borrow().name.clone()).collect();
+        assert_eq!(names, ["n0", "n1", "n3", "n7", "n3", "n8", "n3", "n1"]);
+        for (id, index) in &first {
+            assert_eq!(tour[*index].borrow().id, *id);
+        }
+
+        // LCA by the minimum depth between the first occurrences
+        let depths = BinaryTree::depth_map(&root);
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let lca = |a: usize, b: usize| {
+            let a = first[&nodes[a].borrow().id];
+            let b = first[&nodes[b].borrow().id];
+            tour[a.min(b)..=a.max(b)]
+                .iter()
+                .min_by_key(|n| depths[&n.borrow().id])
+                .map(|n| n.borrow().name.clone())
+                .unwrap()
+        };
+        assert_eq!(lca(7, 10), "n1");
+        assert_eq!(lca(9, 10), "n4");
+        assert_eq!(lca(7, 14), "n0");
+        assert_eq!(lca(5, 12), "n5");
+
+        let (tour, _) = BinaryTree::euler_tour(&BinaryTree::new_node());
+        assert_eq!(tour.len(), 1);
+    }
+
+    #[test]
+    fn centroid() {
+        let root = populate_balanced_binary_tree();
+        assert!(BinaryTree::is_same_node(
+            &BinaryTree::centroid(&root),
+            &root
+        ));
+
+        let root = populate_right_spine(5);
+        assert_eq!(BinaryTree::centroid(&root).borrow().name, "n2");
+
+        let root = populate_right_spine(1);
+        assert!(BinaryTree::is_same_node(
+            &BinaryTree::centroid(&root),
+            &root
+        ));
+    }
+
+    #[test]
+    fn centroid_decomp() {
+        let root = populate_balanced_binary_tree();
+        let decomp = BinaryTree::centroid_decomp(&root);
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        let level = |index: usize| decomp.level(nodes[index].borrow().id);
+        assert_eq!(level(0), 0);
+        assert_eq!(decomp.parent(root.borrow().id), None);
+        assert!((1..=2).all(|i| level(i) == 1));
+        assert!((3..=6).all(|i| level(i) == 2));
+        assert!((7..=14).all(|i| level(i) == 3));
+        assert_eq!(
+            decomp.parent(nodes[3].borrow().id),
+            Some(nodes[1].borrow().id)
+        );
+
+        // a spine of 7 splits like a binary search: n3, then n1 and n5
+        let root = populate_right_spine(7);
+        let decomp = BinaryTree::centroid_decomp(&root);
+        let levels: Vec<_> = BinaryTree::flatten_top_down(root)
+            .iter()
+            .map(|n| decomp.level(n.borrow().id))
+            .collect();
+        assert_eq!(levels, [2, 1, 2, 0, 2, 1, 2]);
+
+        for count in [1, 2, 100, 1000] {
+            let root = populate_right_spine(count);
+            let decomp = BinaryTree::centroid_decomp(&root);
+            let depth = BinaryTree::flatten_top_down(root)
+                .iter()
+                .map(|n| decomp.level(n.borrow().id))
+                .max()
+                .unwrap();
+            assert!(depth <= count.ilog2() as usize);
+        }
+    }
+
+    #[test]
+    fn topological_order() {
+        let root = populate_balanced_binary_tree();
+        let order = BinaryTree::topological_order(&root);
+        assert_eq!(order.len(), NODES_COUNT);
+        assert!(BinaryTree::is_topological_order(&root, &order));
+
+        // preorder also puts parents first, postorder does not
+        let preorder = BinaryTree::flatten_preorder(root.clone());
+        assert!(BinaryTree::is_topological_order(&root, &preorder));
+        let mut reversed = order.clone();
+        reversed.reverse();
+        assert!(!BinaryTree::is_topological_order(&root, &reversed));
+
+        // missing and repeated nodes
+        assert!(!BinaryTree::is_topological_order(&root, &order[1..]));
+        let mut repeated = order.clone();
+        repeated[14] = order[13].clone();
+        assert!(!BinaryTree::is_topological_order(&root, &repeated));
+        let mut foreign = order.clone();
+        foreign[14] = BinaryTree::new_node();
+        assert!(!BinaryTree::is_topological_order(&root, &foreign));
+    }
+
+    #[test]
+    fn from_bracket_notation() {
+        let tree =
+            BinaryTree::from_bracket_notation("8(4(2(1)(3))(6(5)(7)))(12(10(9)(11))(14(13)(15)))")
+                .unwrap();
+        let root = tree.root.as_ref().unwrap();
+        assert!(BinaryTree::same_tree(
+            root,
+            &populate_balanced_binary_search_tree()
+        ));
+        assert_eq!(root.borrow().name, "8");
+        assert_eq!(BinaryTree::validate_parents(root), Ok(()));
+
+        // missing children
+        let tree = BinaryTree::from_bracket_notation("2()(3(4))").unwrap();
+        let root = tree.root.unwrap();
+        assert!(root.borrow().left.is_none());
+        let right = root.borrow().right.clone().unwrap();
+        assert_eq!(right.borrow().data, 3);
+        assert_eq!(right.borrow().left.as_ref().unwrap().borrow().data, 4);
+        assert!(right.borrow().right.is_none());
+    }
+
+    #[test]
+    fn from_bracket_notation_errors() {
+        let parse = |s| BinaryTree::from_bracket_notation(s).err().unwrap();
+        assert_eq!(parse(""), ParseError::MismatchedParens { position: 0 });
+        assert_eq!(parse("8(4"), ParseError::MismatchedParens { position: 3 });
+        assert_eq!(parse("8(4))"), ParseError::MismatchedParens { position: 4 });
+        assert_eq!(parse("8("), ParseError::MismatchedParens { position: 2 });
+        assert_eq!(parse("8(x)"), ParseError::InvalidNumber { position: 2 });
+        assert_eq!(parse("(1)"), ParseError::InvalidNumber { position: 0 });
+        assert_eq!(
+            parse("99999999999"),
+            ParseError::InvalidNumber { position: 0 }
+        );
+        assert_eq!(
+            parse("8(4)(5)(6)"),
+            ParseError::UnexpectedChar {
+                position: 7,
+                found: '('
+            }
+        );
+        assert_eq!(
+            parse("8(4x)"),
+            ParseError::UnexpectedChar {
+                position: 3,
+                found: 'x'
+            }
+        );
+    }
+
+    #[test]
+    fn to_bracket_notation() {
+        let root = populate_balanced_binary_search_tree();
+        let notation = BinaryTree::to_bracket_notation(&root);
+        assert_eq!(
+            notation,
+            "8(4(2(1)(3))(6(5)(7)))(12(10(9)(11))(14(13)(15)))"
+        );
+
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_balanced_binary_search_tree(),
+            populate_right_spine(4),
+        ] {
+            let notation = BinaryTree::to_bracket_notation(&root);
+            let tree = BinaryTree::from_bracket_notation(&notation).unwrap();
+            assert!(BinaryTree::same_tree(tree.root.as_ref().unwrap(), &root));
+        }
+
+        assert_eq!(
+            BinaryTree::to_bracket_notation(&populate_right_spine(3)),
+            "0()(1()(2))"
+        );
+        let tree = BinaryTreeBuilder::new()
+            .root(2, "a")
+            .left(1, "b")
+            .build()
+            .unwrap();
+        assert_eq!(
+            BinaryTree::to_bracket_notation(tree.root.as_ref().unwrap()),
+            "2(1)"
+        );
+    }
+
+    #[test]
+    fn flatten_postorder_stack() {
+        fn postorder(node: &Option<BinaryTreeNodeRef>, names: &mut Vec<String>) {
+            if let Some(node_ref) = node {
+                let node = node_ref.borrow();
+                postorder(&node.left, names);
+                postorder(&node.right, names);
+                names.push(node.name.clone());
+            }
+        }
+
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_balanced_binary_search_tree(),
+            populate_right_spine(4),
+        ] {
+            let mut expected = Vec::new();
+            postorder(&Some(root.clone()), &mut expected);
+            let names: Vec<_> = BinaryTree::flatten_postorder_stack(root)
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect();
+            assert_eq!(names, expected);
+        }
+
+        let root = populate_balanced_binary_tree();
+        let names: Vec<_> = BinaryTree::flatten_postorder_stack(root)
+            .iter()
+            .take(7)
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, ["n7", "n8", "n3", "n9", "n10", "n4", "n1"]);
+    }
+
+    #[test]
+    fn find_all_by_data() {
+        let tree = BinaryTreeBuilder::new()
+            .root(1, "a")
+            .left(2, "b")
+            .left(1, "c")
+            .up()
+            .right(2, "d")
+            .up()
+            .up()
+            .right(1, "e")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        let names = |data: u32| -> Vec<_> {
+            BinaryTree::find_all_by_data(&root, data)
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect()
+        };
+        assert_eq!(names(1), ["a", "e", "c"]);
+        assert_eq!(names(2), ["b", "d"]);
+        assert!(names(3).is_empty());
+
+        let root = populate_balanced_binary_search_tree();
+        for data in 0..=16 {
+            let found = BinaryTree::find_all_by_data(&root, data);
+            assert_eq!(found.len(), usize::from((1..=15).contains(&data)));
+            assert!(found.iter().all(|n| n.borrow().data == data));
+        }
+    }
+
+    #[test]
+    fn replace_all_by_data() {
+        let tree: BinaryTree = [5, 3, 5, 8, 3, 5]
+            .iter()
+            .map(|v| (*v, format!("n{}", v)))
+            .collect();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::replace_all_by_data(&root, 5, 7), 3);
+        assert!(BinaryTree::find_all_by_data(&root, 5).is_empty());
+        assert_eq!(BinaryTree::find_all_by_data(&root, 7).len(), 3);
+        assert_eq!(BinaryTree::find_all_by_data(&root, 3).len(), 2);
+
+        assert_eq!(BinaryTree::replace_all_by_data(&root, 42, 1), 0);
+        // replacing with the same value still counts the matches
+        assert_eq!(BinaryTree::replace_all_by_data(&root, 3, 3), 2);
+        assert_eq!(BinaryTree::count(&root), 6);
+    }
+
+    #[test]
+    fn prune_leaves() {
+        let root = populate_balanced_binary_tree();
+        let mut current = root.clone();
+        for expected in [7, 3, 1] {
+            current = BinaryTree::prune_leaves(&current).unwrap();
+            assert_eq!(BinaryTree::count(&current), expected);
+            assert_eq!(BinaryTree::validate_parents(&current), Ok(()));
+        }
+        assert_eq!(current.borrow().name, "n0");
+        assert!(BinaryTree::prune_leaves(&current).is_none());
+
+        // the original is left untouched
+        assert_eq!(BinaryTree::count(&root), NODES_COUNT);
+
+        let names: Vec<_> = BinaryTree::flatten_top_down(BinaryTree::prune_leaves(&root).unwrap())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, ["n0", "n1", "n2", "n3", "n4", "n5", "n6"]);
+    }
+
+    #[test]
+    fn graft() {
+        let tree = BinaryTree::with_root(populate_balanced_binary_tree());
+        let nodes = BinaryTree::flatten_top_down(tree.root.clone().unwrap());
+        let n3 = nodes[3].clone();
+        let n7 = nodes[7].clone();
+
+        let branch = BinaryTree::with_root(populate_right_spine(3));
+        let branch_root = branch.root.clone().unwrap();
+        let target_id = n3.borrow().id;
+        assert_eq!(
+            BinaryTree::graft(&tree, target_id, Direction::Left, branch),
+            Ok(())
+        );
+
+        let root = tree.root.as_ref().unwrap();
+        assert_eq!(BinaryTree::count(root), NODES_COUNT - 1 + 3);
+        assert!(BinaryTree::is_same_node(
+            n3.borrow().left.as_ref().unwrap(),
+            &branch_root
+        ));
+        assert_eq!(BinaryTree::validate_parents(root), Ok(()));
+        assert!(BinaryTree::is_same_node(
+            &BinaryTree::get_root(&branch_root),
+            root
+        ));
+        // the replaced child is detached
+        assert!(n7.borrow().parent.upgrade().is_none());
+
+        // an empty branch removes the child
+        let target_id = nodes[6].borrow().id;
+        BinaryTree::graft(&tree, target_id, Direction::Right, BinaryTree::new()).unwrap();
+        assert!(nodes[6].borrow().right.is_none());
+        assert_eq!(BinaryTree::count(root), NODES_COUNT - 1 + 3 - 1);
+
+        assert_eq!(
+            BinaryTree::graft(&tree, Uuid::new_v4(), Direction::Left, BinaryTree::new()),
+            Err(GraftError::TargetNotFound)
+        );
+        assert_eq!(
+            BinaryTree::graft(
+                &BinaryTree::new(),
+                target_id,
+                Direction::Left,
+                BinaryTree::new()
+            ),
+            Err(GraftError::TargetNotFound)
+        );
+    }
+
+    #[test]
+    fn find_duplicate_subtrees() {
+        /*
+                1
+               / \
+              2   3
+             /   / \
+            4   2   4
+               /
+              4
+        */
+        let tree = BinaryTreeBuilder::new()
+            .root(1, "a")
+            .left(2, "b")
+            .left(4, "c")
+            .up()
+            .up()
+            .right(3, "d")
+            .left(2, "e")
+            .left(4, "f")
+            .up()
+            .up()
+            .right(4, "g")
+            .build()
+            .unwrap();
+        let root = tree.root.unwrap();
+        let duplicates = BinaryTree::find_duplicate_subtrees(&root);
+        let data: Vec<_> = duplicates.iter().map(|n| n.borrow().data).collect();
+        assert_eq!(data, [4, 2]);
+        // one representative each for the single 4 and for 2 -> 4
+        assert!(BinaryTree::same_tree(
+            &duplicates[1],
+            root.borrow().left.as_ref().unwrap()
+        ));
+
+        // the leaves of the balanced fixture all hold different data
+        let root = populate_balanced_binary_tree();
+        assert!(BinaryTree::find_duplicate_subtrees(&root).is_empty());
+
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().data = 0);
+        let duplicates = BinaryTree::find_duplicate_subtrees(&root);
+        let sizes: Vec<_> = duplicates.iter().map(BinaryTree::count).collect();
+        assert_eq!(sizes, [1, 3, 7]);
+    }
+
+    #[test]
+    fn closest_value() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::closest_value(&root, 7.5), 8);
+        assert_eq!(BinaryTree::closest_value(&root, 7.4), 7);
+        assert_eq!(BinaryTree::closest_value(&root, 0.0), 1);
+        assert_eq!(BinaryTree::closest_value(&root, 100.0), 15);
+        assert_eq!(BinaryTree::closest_value(&root, 10.6), 11);
+        for data in 1..=15 {
+            assert_eq!(BinaryTree::closest_value(&root, data as f64), data);
+        }
+    }
+
+    #[test]
+    fn kth_ancestor() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root);
+        let ancestor = |index: usize, k: usize| {
+            BinaryTree::kth_ancestor(&nodes[index], k).map(|n| n.borrow().name.clone())
+        };
+        assert_eq!(ancestor(10, 1), Some("n4".to_string()));
+        assert_eq!(ancestor(10, 2), Some("n1".to_string()));
+        assert_eq!(ancestor(10, 3), Some("n0".to_string()));
+        assert_eq!(ancestor(10, 4), None);
+        for leaf in 7..=14 {
+            assert_eq!(ancestor(leaf, 3), Some("n0".to_string()));
+            assert_eq!(ancestor(leaf, 4), None);
+        }
+        assert_eq!(ancestor(13, 0), Some("n13".to_string()));
+        assert_eq!(ancestor(0, 1), None);
+    }
 
-        let n10 = n4.right.as_ref().unwrap().borrow();
-        assert_eq!(n10.name, "n10".to_string());
+    #[test]
+    fn from_leetcode_str() {
+        let tree = BinaryTree::from_leetcode_str("1,2,3,null,null,4,5").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::to_bracket_notation(&root), "1(2)(3(4)(5))");
+        assert_eq!(BinaryTree::validate_parents(&root), Ok(()));
+
+        let tree = BinaryTree::from_leetcode_str("[5, null, 7, 6]").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::to_bracket_notation(&root), "5()(7(6))");
+
+        assert!(BinaryTree::from_leetcode_str("").unwrap().is_empty());
+        assert!(BinaryTree::from_leetcode_str("[]").unwrap().is_empty());
+        assert!(BinaryTree::from_leetcode_str("null").unwrap().is_empty());
+
+        let parse = |s| BinaryTree::from_leetcode_str(s).err().unwrap();
+        assert_eq!(parse("1,x,3"), ParseError::InvalidNumber { position: 2 });
         assert_eq!(
-            n10.parent.upgrade().unwrap().borrow().name,
-            "n4".to_string()
+            parse("[1, 2,,3]"),
+            ParseError::InvalidNumber { position: 6 }
         );
-
-        let n11 = n5.left.as_ref().unwrap().borrow();
-        assert_eq!(n11.name, "n11".to_string());
+        assert_eq!(parse("[1,2"), ParseError::MismatchedParens { position: 4 });
+        assert_eq!(parse("1,2]"), ParseError::MismatchedParens { position: 3 });
         assert_eq!(
-            n11.parent.upgrade().unwrap().borrow().name,
-            "n5".to_string()
+            parse("1,null,null,2"),
+            ParseError::UnexpectedValue { position: 12 }
         );
-
-        let n12 = n5.right.as_ref().unwrap().borrow();
-        assert_eq!(n12.name, "n12".to_string());
         assert_eq!(
-            n12.parent.upgrade().unwrap().borrow().name,
-            "n5".to_string()
+            parse("null,1,2"),
+            ParseError::UnexpectedValue { position: 5 }
         );
-
-        let n13 = n6.left.as_ref().unwrap().borrow();
-        assert_eq!(n13.name, "n13".to_string());
         assert_eq!(
-            n13.parent.upgrade().unwrap().borrow().name,
-            "n6".to_string()
+            parse("[null, null]"),
+            ParseError::UnexpectedValue { position: 7 }
         );
+    }
+
+    #[test]
+    fn to_leetcode_str() {
+        let root = populate_balanced_binary_search_tree();
+        let s = BinaryTree::to_leetcode_str(&root);
+        assert_eq!(s, "8,4,12,2,6,10,14,1,3,5,7,9,11,13,15");
+        let tree = BinaryTree::from_leetcode_str(&s).unwrap();
+        assert!(BinaryTree::same_tree(tree.root.as_ref().unwrap(), &root));
+
+        let root = populate_right_spine(3);
+        let s = BinaryTree::to_leetcode_str(&root);
+        assert_eq!(s, "0,null,1,null,2");
+        let tree = BinaryTree::from_leetcode_str(&s).unwrap();
+        assert!(BinaryTree::same_tree(tree.root.as_ref().unwrap(), &root));
+    }
+
+    #[test]
+    fn count_good_nodes() {
+        // in a BST the left children are smaller than their parents,
+        // only the right spine 8 -> 12 -> 14 -> 15 is good
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::count_good_nodes(&root), 4);
+
+        // ancestors never greater than descendants
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::count_good_nodes(&root), NODES_COUNT);
+
+        let tree = BinaryTree::from_leetcode_str("3,1,4,3,null,1,5").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::count_good_nodes(&root), 4);
+
+        let tree = BinaryTree::from_leetcode_str("3,3,null,4,2").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::count_good_nodes(&root), 3);
+
+        let tree = BinaryTree::from_leetcode_str("5,3,10,20,21,1").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::count_good_nodes(&root), 4);
+
+        assert_eq!(BinaryTree::count_good_nodes(&populate_right_spine(5)), 5);
+    }
+
+    #[test]
+    fn max_ancestor_difference() {
+        // 1 and 15 are on different sides of the root 8, the best paths are 8 -> 1 and 8 -> 15
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::max_ancestor_difference(&root), 7);
+
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::max_ancestor_difference(&root), 14);
+
+        let tree = BinaryTree::from_leetcode_str("8,3,10,1,6,null,14,null,null,4,7,13").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::max_ancestor_difference(&root), 7);
 
-        let n14 = n6.right.as_ref().unwrap().borrow();
-        assert_eq!(n14.name, "n14".to_string());
         assert_eq!(
-            n14.parent.upgrade().unwrap().borrow().name,
-            "n6".to_string()
+            BinaryTree::max_ancestor_difference(&BinaryTree::new_node()),
+            0
         );
     }
 
     #[test]
-    fn binary_tree_populate_test2() {
+    fn distribute_coins_moves() {
+        let moves = |s| {
+            let tree = BinaryTree::from_leetcode_str(s).unwrap();
+            BinaryTree::distribute_coins_moves(tree.root.as_ref().unwrap())
+        };
+        assert_eq!(moves("3,0,0"), 2);
+        assert_eq!(moves("0,3,0"), 3);
+        assert_eq!(moves("1,0,2"), 2);
+        // 3 coins at the bottom: two go up past 0 and one of them on to the other side
+        assert_eq!(moves("1,0,0,null,3"), 4);
+        assert_eq!(moves("1,1,1"), 0);
+    }
+
+    #[test]
+    fn min_cameras() {
+        // one on each parent of the leaves, plus the root
         let root = populate_balanced_binary_tree();
-        let nodes = BinaryTree::flatten_top_down(root);
-        let nodes_count = nodes.len();
-        assert_eq!(nodes_count, NODES_COUNT);
+        assert_eq!(BinaryTree::min_cameras(&root), 5);
 
-        for (index, node_ref) in nodes.iter().enumerate() {
-            let node = node_ref.borrow();
-            assert_eq!(node.name, format!("n{index}"));
-            if index == 0 {
-                assert_eq!(node.parent.upgrade(), None);
-            } else {
-                let parent_position = if index % 2 != 0 { 1 } else { 2 };
-                let parent = (index - parent_position) / 2;
-                assert_eq!(
-                    node.parent.upgrade().unwrap().borrow().name,
-                    format!("n{}", parent)
-                );
-            }
-            let left = index * 2 + 1;
-            if left < nodes_count {
-                assert_eq!(
-                    node.left.as_ref().unwrap().borrow().name,
-                    format!("n{}", left)
-                );
-            }
-            let right = left + 1;
-            if left < nodes_count {
-                assert_eq!(
-                    node.right.as_ref().unwrap().borrow().name,
-                    format!("n{}", right)
-                );
-            }
-        }
+        assert_eq!(BinaryTree::min_cameras(&BinaryTree::new_node()), 1);
+        assert_eq!(BinaryTree::min_cameras(&populate_right_spine(2)), 1);
+        assert_eq!(BinaryTree::min_cameras(&populate_right_spine(3)), 1);
+        assert_eq!(BinaryTree::min_cameras(&populate_right_spine(5)), 2);
+
+        let cameras = |s| {
+            let tree = BinaryTree::from_leetcode_str(s).unwrap();
+            BinaryTree::min_cameras(tree.root.as_ref().unwrap())
+        };
+        assert_eq!(cameras("0,0,null,0,0"), 1);
+        assert_eq!(cameras("0,0,null,0,null,0,null,null,0"), 2);
     }
 
     #[test]
-    fn count() {
-        let node = populate_balanced_binary_tree();
-        assert_eq!(BinaryTree::count(&node), NODES_COUNT);
+    fn left_boundary() {
+        let names = |root: &BinaryTreeNodeRef| -> Vec<_> {
+            BinaryTree::left_boundary(root)
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect()
+        };
+        assert_eq!(names(&populate_balanced_binary_tree()), ["n0", "n1", "n3"]);
+
+        let tree = BinaryTree::from_leetcode_str("0,1,null,2,null,3").unwrap();
+        assert_eq!(names(tree.root.as_ref().unwrap()), ["0", "1", "2"]);
+
+        // without a left child the boundary goes on to the right
+        assert_eq!(names(&populate_right_spine(4)), ["n0", "n1", "n2"]);
+        assert_eq!(names(&BinaryTree::new_node()), [""]);
     }
 
     #[test]
-    fn flatten_top_down() {
-        let root = populate_balanced_binary_search_tree();
+    fn right_boundary() {
+        let names = |root: &BinaryTreeNodeRef| -> Vec<_> {
+            BinaryTree::right_boundary(root)
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect()
+        };
+        assert_eq!(names(&populate_balanced_binary_tree()), ["n0", "n2", "n6"]);
+        assert_eq!(names(&populate_right_spine(4)), ["n0", "n1", "n2"]);
 
-        let flatten_nodes = BinaryTree::flatten_top_down(root);
-        assert_eq!(flatten_nodes.len(), NODES_COUNT);
+        // without a right child the boundary goes on to the left
+        let tree = BinaryTree::from_leetcode_str("0,1,null,2,3").unwrap();
+        assert_eq!(names(tree.root.as_ref().unwrap()), ["0", "1"]);
+    }
 
-        let flatten_names: Vec<_> = flatten_nodes
+    #[test]
+    fn is_foldable() {
+        let root = populate_balanced_binary_tree();
+        assert!(BinaryTree::is_foldable(&root));
+
+        // an extra level on the left side only
+        let nodes = BinaryTree::flatten_top_down(root.clone());
+        nodes[7].borrow_mut().left = Some(BinaryTree::new_node());
+        assert!(!BinaryTree::is_foldable(&root));
+        // and its mirror on the right side
+        nodes[14].borrow_mut().right = Some(BinaryTree::new_node());
+        assert!(BinaryTree::is_foldable(&root));
+
+        let is_foldable = |s| {
+            let tree = BinaryTree::from_leetcode_str(s).unwrap();
+            BinaryTree::is_foldable(tree.root.as_ref().unwrap())
+        };
+        assert!(is_foldable("1,2,3,null,4,5"));
+        assert!(!is_foldable("1,2,3,4,null,5"));
+        assert!(!is_foldable("1,2"));
+        assert!(is_foldable("1"));
+    }
+
+    #[test]
+    fn reverse_alternate_levels() {
+        let root = populate_balanced_binary_search_tree();
+        BinaryTree::reverse_alternate_levels(&root);
+        let data: Vec<_> = BinaryTree::flatten_top_down(root.clone())
             .iter()
-            .map(|n| n.borrow().name.clone())
+            .map(|n| n.borrow().data)
             .collect();
+        assert_eq!(data, [8, 12, 4, 2, 6, 10, 14, 15, 13, 11, 9, 7, 5, 3, 1]);
 
-        let expected_names = (0..NODES_COUNT)
-            .map(|n| format!("n{}", n))
-            .collect::<Vec<_>>();
-        assert_eq!(flatten_names, expected_names);
+        // twice gives back the original
+        BinaryTree::reverse_alternate_levels(&root);
+        assert!(BinaryTree::same_tree(
+            &root,
+            &populate_balanced_binary_search_tree()
+        ));
     }
 
     #[test]
-    fn leftmost() {
-        let expected = HashMap::from([
-            ("n0", Some("n7")),
-            ("n1", Some("n7")),
-            ("n2", Some("n11")),
-            ("n3", Some("n7")),
-            ("n4", Some("n9")),
-            ("n5", Some("n11")),
-            ("n6", Some("n13")),
-            ("n7", None),
-            ("n8", None),
-            ("n9", None),
-            ("n10", None),
-            ("n11", None),
-            ("n12", None),
-            ("n13", None),
-            ("n14", None),
-        ]);
+    fn print_ancestors() {
+        let root = populate_balanced_binary_tree();
+        let nodes = BinaryTree::flatten_top_down(root);
+        let ancestors = |index: usize| {
+            let mut out = Vec::<u8>::new();
+            BinaryTree::print_ancestors(&nodes[index], &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        for leaf in 7..=14 {
+            let parent = (leaf - 1) / 2;
+            let grandparent = (parent - 1) / 2;
+            let expected = format!("n0 n{} n{}\n", grandparent, parent);
+            assert_eq!(ancestors(leaf), expected);
+        }
+        assert_eq!(ancestors(2), "n0\n");
+        assert_eq!(ancestors(0), "\n");
+    }
 
+    #[test]
+    fn are_mirrors() {
         let root = populate_balanced_binary_tree();
-        let flatten_nodes = BinaryTree::flatten_top_down(root);
-        assert_eq!(flatten_nodes.len(), expected.len());
-        let mut flatten_names: Vec<_> = flatten_nodes
-            .iter()
-            .map(|n| n.borrow().name.clone())
-            .collect();
+        let mirror = BinaryTree::mirror_copy(&root);
+        assert!(BinaryTree::are_mirrors(&root, &mirror));
+        assert!(BinaryTree::are_mirrors(&mirror, &root));
+        // not a mirror of itself, the data differs between the sides
+        assert!(!BinaryTree::are_mirrors(&root, &root));
 
-        let mut expected_names = expected.iter().map(|v| v.0.to_string()).collect::<Vec<_>>();
-        flatten_names.sort();
-        expected_names.sort();
-        assert_eq!(flatten_names, expected_names);
+        let nodes = BinaryTree::flatten_top_down(mirror.clone());
+        nodes[9].borrow_mut().data += 100;
+        assert!(!BinaryTree::are_mirrors(&root, &mirror));
+        nodes[9].borrow_mut().data -= 100;
+        nodes[9].borrow_mut().left = Some(BinaryTree::new_node());
+        assert!(!BinaryTree::are_mirrors(&root, &mirror));
 
-        for node_ref in flatten_nodes {
-            let node = node_ref.borrow();
-            let leftmost = BinaryTree::leftmost(&node_ref);
-            let name;
-            assert_eq!(
-                expected[node.name.as_str()],
-                match leftmost {
-                    Some(left_ref) => {
-                        let node = left_ref.borrow();
-                        name = node.name.clone();
-                        Some(name.as_str())
-                    }
-                    None => None,
-                }
-            );
-        }
+        let root = populate_right_spine(3);
+        let mirror = BinaryTree::mirror_copy(&root);
+        assert!(BinaryTree::are_mirrors(&root, &mirror));
+        assert!(!BinaryTree::are_mirrors(&root, &populate_right_spine(3)));
     }
 
     #[test]
-    fn flatten_inorder() {
-        let expected = [
-            "n7", "n3", "n8", "n1", "n9", "n4", "n10", "n0", "n11", "n5", "n12", "n2", "n13", "n6",
-            "n14",
-        ];
+    fn sum_even_grandparent() {
+        // 8, 4 and 12 are even: 2 + 6 + 10 + 14 and 1 + 3 + ... + 15 below them
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::sum_even_grandparent(&root), 32 + 64);
 
+        // n0 and n2 are the even nodes with grandchildren: n3..n6 and n11..n14
         let root = populate_balanced_binary_tree();
-        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
-        assert_eq!(flatten_nodes.len(), expected.len());
+        assert_eq!(
+            BinaryTree::sum_even_grandparent(&root),
+            (3..=6).sum::<u64>() + (11..=14).sum::<u64>()
+        );
 
-        let flatten_names: Vec<_> = flatten_nodes
+        assert_eq!(
+            BinaryTree::sum_even_grandparent(&populate_right_spine(2)),
+            0
+        );
+    }
+
+    #[test]
+    fn deepest_leaves_sum() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::deepest_leaves_sum(&root), 64);
+
+        // 7 and 8 are the deepest leaves, the leaf 5 is one level up
+        let tree =
+            BinaryTree::from_leetcode_str("1,2,3,4,5,null,6,7,null,null,null,null,8").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::deepest_leaves_sum(&root), 15);
+
+        assert_eq!(BinaryTree::deepest_leaves_sum(&populate_right_spine(4)), 3);
+    }
+
+    #[test]
+    fn to_greater_sum_tree() {
+        let root = populate_balanced_binary_search_tree();
+        let original: Vec<_> = BinaryTree::flatten_top_down(root.clone())
             .iter()
-            .map(|n| n.borrow().name.clone())
+            .map(|n| n.borrow().data)
             .collect();
-        assert_eq!(flatten_names, expected);
+        BinaryTree::to_greater_sum_tree(&root);
+        let data: Vec<_> = BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .map(|n| n.borrow().data)
+            .collect();
+        assert_eq!(data[..3], [92, 114, 54]);
+        // v becomes v + (v + 1) + ... + 15
+        for (before, after) in original.iter().zip(&data) {
+            assert_eq!(*after, (*before..=15).sum::<u32>());
+        }
+
+        let tree = BinaryTree::from_leetcode_str("3000000000,1000000000,4000000000").unwrap();
+        let root = tree.root.unwrap();
+        BinaryTree::to_greater_sum_tree(&root);
+        assert_eq!(
+            BinaryTree::to_leetcode_str(&root),
+            "4294967295,4294967295,4000000000"
+        );
     }
 
     #[test]
-    fn invert_recursive() {
-        let expected = [
-            "n14", "n6", "n13", "n2", "n12", "n5", "n11", "n0", "n10", "n4", "n9", "n1", "n8",
-            "n3", "n7",
-        ];
-
-        let root = populate_balanced_binary_tree();
-        BinaryTree::invert_recursive(&root);
+    fn bst_insert_iterative() {
+        // xorshift, enough to get 100 scattered values with repeats
+        let mut state = 0x2545_f491_u32;
+        let values: Vec<_> = (0..100)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state % 64
+            })
+            .collect();
 
-        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
-        assert_eq!(flatten_nodes.len(), expected.len());
+        let recursive = BinaryTree::new_node_with_data(values[0], "n0");
+        let iterative = BinaryTree::new_node_with_data(values[0], "n0");
+        for (index, data) in values.iter().enumerate().skip(1) {
+            let name = format!("n{}", index);
+            BinaryTree::bst_insert(&recursive, *data, &name);
+            let node = BinaryTree::bst_insert_iterative(&iterative, *data, &name);
+            assert_eq!(node.borrow().data, *data);
+        }
 
-        let flatten_names: Vec<_> = flatten_nodes
+        assert_eq!(BinaryTree::count(&iterative), 100);
+        assert!(BinaryTree::same_tree(&recursive, &iterative));
+        // `is_valid_bst` rejects the repeated values, the inorder is sorted nevertheless
+        let inorder: Vec<_> = BinaryTree::flatten_inorder_stack(iterative.clone())
             .iter()
-            .map(|n| n.borrow().name.clone())
+            .map(|n| n.borrow().data)
             .collect();
-        assert_eq!(flatten_names, expected);
+        assert!(inorder.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(BinaryTree::validate_parents(&iterative), Ok(()));
+        let names = |root: &BinaryTreeNodeRef| -> Vec<_> {
+            BinaryTree::flatten_top_down(root.clone())
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect()
+        };
+        assert_eq!(names(&recursive), names(&iterative));
     }
 
     #[test]
-    fn invert_iterative() {
-        let expected = [
-            "n14", "n6", "n13", "n2", "n12", "n5", "n11", "n0", "n10", "n4", "n9", "n1", "n8",
-            "n3", "n7",
-        ];
+    fn find_mode() {
+        let root = BinaryTree::new_node_with_data(5, "n0");
+        for (index, data) in [3, 7, 3, 7, 2, 5, 3, 7, 8].iter().enumerate() {
+            BinaryTree::bst_insert(&root, *data, &format!("n{}", index + 1));
+        }
+        let original = BinaryTree::clone_subtree(&root);
+        assert_eq!(BinaryTree::find_mode(&root), [3, 7]);
+        // the temporary threads are removed again
+        assert!(BinaryTree::same_tree(&root, &original));
+
+        BinaryTree::bst_insert(&root, 7, "n10");
+        assert_eq!(BinaryTree::find_mode(&root), [7]);
+
+        // no duplicates: every value is a mode
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::find_mode(&root), (1..=15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn min_abs_diff_bst() {
+        // the inorder is 1, 2, ..., 15
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::min_abs_diff_bst(&root), 1);
+
+        let tree = BinaryTree::from_leetcode_str("40,10,90,null,30,60,120").unwrap();
+        let root = tree.root.unwrap();
+        assert_eq!(BinaryTree::min_abs_diff_bst(&root), 10);
+
+        BinaryTree::bst_insert(&root, 60, "dup");
+        assert_eq!(BinaryTree::min_abs_diff_bst(&root), 0);
+
+        let root = BinaryTree::new_node();
+        assert_eq!(BinaryTree::min_abs_diff_bst(&root), u32::MAX);
+    }
+
+    #[test]
+    fn range_sum_bst() {
+        let root = populate_balanced_binary_search_tree();
+        assert_eq!(BinaryTree::range_sum_bst(&root, 7, 15), 99);
+        assert_eq!(BinaryTree::range_sum_bst(&root, 1, 15), 120);
+        assert_eq!(BinaryTree::range_sum_bst(&root, 0, 3), 6);
+        assert_eq!(BinaryTree::range_sum_bst(&root, 8, 8), 8);
+        assert_eq!(BinaryTree::range_sum_bst(&root, 16, 100), 0);
+        assert_eq!(BinaryTree::range_sum_bst(&root, 10, 5), 0);
+    }
+
+    #[test]
+    fn delete_leaves_with_value() {
+        let tree = BinaryTree::from_leetcode_str("1,2,3,2,null,2,4").unwrap();
+        let root = tree.root.unwrap();
+        let root = BinaryTree::delete_leaves_with_value(&root, 2).unwrap();
+        assert_eq!(BinaryTree::to_leetcode_str(&root), "1,null,3,null,4");
+
+        // the removal goes up the chain of 2s
+        let tree = BinaryTree::from_leetcode_str("1,2,null,2,null,2").unwrap();
+        let root = tree.root.unwrap();
+        let root = BinaryTree::delete_leaves_with_value(&root, 2).unwrap();
+        assert_eq!(BinaryTree::count(&root), 1);
+
+        let tree = BinaryTree::from_leetcode_str("1,1,1").unwrap();
+        let root = tree.root.unwrap();
+        assert!(BinaryTree::delete_leaves_with_value(&root, 1).is_none());
 
         let root = populate_balanced_binary_tree();
-        BinaryTree::invert_iterative(root.clone());
+        let root = BinaryTree::delete_leaves_with_value(&root, 3).unwrap();
+        assert_eq!(BinaryTree::count(&root), NODES_COUNT);
+    }
 
-        let flatten_nodes: Vec<_> = BinaryTree::flatten_inorder(root.clone());
-        assert_eq!(flatten_nodes.len(), expected.len());
+    #[test]
+    fn sum_of_all_depths() {
+        let root = populate_balanced_binary_tree();
+        assert_eq!(BinaryTree::sum_of_all_depths(&root), 34);
 
-        let flatten_names: Vec<_> = flatten_nodes
-            .iter()
-            .map(|n| n.borrow().name.clone())
-            .collect();
-        assert_eq!(flatten_names, expected);
+        // 0 + 1 + 2 + 3 + 4
+        assert_eq!(BinaryTree::sum_of_all_depths(&populate_right_spine(5)), 10);
+        assert_eq!(BinaryTree::sum_of_all_depths(&BinaryTree::new_node()), 0);
+    }
+
+    #[test]
+    fn average_depth() {
+        let root = populate_balanced_binary_tree();
+        let average = BinaryTree::average_depth(&root);
+        assert!((average - 34.0 / 15.0).abs() < 1e-9);
+        assert!((average - 2.267).abs() < 1e-3);
+
+        assert_eq!(BinaryTree::average_depth(&BinaryTree::new_node()), 0.0);
+        assert_eq!(BinaryTree::average_depth(&populate_right_spine(5)), 2.0);
     }
 }
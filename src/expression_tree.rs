@@ -0,0 +1,152 @@
+// https://en.wikipedia.org/wiki/Binary_expression_tree
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Leaf,
+}
+
+// same layout as `BinaryTreeNode`, with an operator (or a leaf value) as the payload
+#[derive(Debug)]
+pub struct ExprNode {
+    pub op: Op,
+    pub value: Option<i64>,
+    pub left: Option<ExprNodeRef>,
+    pub right: Option<ExprNodeRef>,
+}
+
+pub type ExprNodeRef = Rc<RefCell<ExprNode>>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EvalError {
+    DivisionByZero,
+    Overflow,
+    MissingOperand,
+    MissingValue,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            EvalError::DivisionByZero => "division by zero",
+            EvalError::Overflow => "arithmetic overflow",
+            EvalError::MissingOperand => "operator without two operands",
+            EvalError::MissingValue => "leaf without a value",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+pub struct ExprTree {
+    pub root: Option<ExprNodeRef>,
+}
+
+impl ExprTree {
+    pub fn with_root(root: ExprNodeRef) -> Self {
+        ExprTree { root: Some(root) }
+    }
+
+    pub fn leaf(value: i64) -> ExprNodeRef {
+        Rc::new(RefCell::new(ExprNode {
+            op: Op::Leaf,
+            value: Some(value),
+            left: None,
+            right: None,
+        }))
+    }
+
+    pub fn operation(op: Op, left: ExprNodeRef, right: ExprNodeRef) -> ExprNodeRef {
+        Rc::new(RefCell::new(ExprNode {
+            op,
+            value: None,
+            left: Some(left),
+            right: Some(right),
+        }))
+    }
+
+    pub fn evaluate(root: &ExprNodeRef) -> Result<i64, EvalError> {
+        let node = root.borrow();
+        if node.op == Op::Leaf {
+            return node.value.ok_or(EvalError::MissingValue);
+        }
+
+        let (Some(left), Some(right)) = (node.left.as_ref(), node.right.as_ref()) else {
+            return Err(EvalError::MissingOperand);
+        };
+        let left = Self::evaluate(left)?;
+        let right = Self::evaluate(right)?;
+
+        let result = match node.op {
+            Op::Add => left.checked_add(right),
+            Op::Sub => left.checked_sub(right),
+            Op::Mul => left.checked_mul(right),
+            Op::Div if right == 0 => return Err(EvalError::DivisionByZero),
+            Op::Div => left.checked_div(right),
+            Op::Leaf => unreachable!(),
+        };
+        result.ok_or(EvalError::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_leaf() {
+        assert_eq!(ExprTree::evaluate(&ExprTree::leaf(-7)), Ok(-7));
+    }
+
+    #[test]
+    fn evaluate() {
+        // (3 + 4) * (5 - 2)
+        let root = ExprTree::operation(
+            Op::Mul,
+            ExprTree::operation(Op::Add, ExprTree::leaf(3), ExprTree::leaf(4)),
+            ExprTree::operation(Op::Sub, ExprTree::leaf(5), ExprTree::leaf(2)),
+        );
+        let tree = ExprTree::with_root(root);
+        assert_eq!(ExprTree::evaluate(tree.root.as_ref().unwrap()), Ok(21));
+
+        let root = ExprTree::operation(Op::Div, ExprTree::leaf(-9), ExprTree::leaf(2));
+        assert_eq!(ExprTree::evaluate(&root), Ok(-4));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        // 10 + 1 / (2 - 2)
+        let root = ExprTree::operation(
+            Op::Add,
+            ExprTree::leaf(10),
+            ExprTree::operation(
+                Op::Div,
+                ExprTree::leaf(1),
+                ExprTree::operation(Op::Sub, ExprTree::leaf(2), ExprTree::leaf(2)),
+            ),
+        );
+        assert_eq!(ExprTree::evaluate(&root), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn evaluate_errors() {
+        let root = ExprTree::operation(Op::Mul, ExprTree::leaf(i64::MAX), ExprTree::leaf(2));
+        assert_eq!(ExprTree::evaluate(&root), Err(EvalError::Overflow));
+
+        let root = ExprTree::leaf(1);
+        root.borrow_mut().op = Op::Add;
+        assert_eq!(ExprTree::evaluate(&root), Err(EvalError::MissingOperand));
+
+        let root = ExprTree::leaf(1);
+        root.borrow_mut().value = None;
+        assert_eq!(ExprTree::evaluate(&root), Err(EvalError::MissingValue));
+    }
+}
@@ -0,0 +1,182 @@
+// https://www.programiz.com/dsa/huffman-coding
+
+use crate::binary_tree::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Leaves keep the symbol in `name` and its frequency in `data`,
+// inner nodes keep the sum of the frequencies below them.
+// A left step is encoded as `false`, a right step as `true`.
+pub struct HuffmanTree {
+    pub tree: BinaryTree,
+}
+
+impl HuffmanTree {
+    pub fn build(frequencies: &[(&str, usize)]) -> HuffmanTree {
+        let mut nodes: Vec<Option<BinaryTreeNodeRef>> = Vec::new();
+        // (frequency, node index), the index keeps the order of equal frequencies stable
+        let mut heap = BinaryHeap::new();
+        for (symbol, frequency) in frequencies {
+            let data = u32::try_from(*frequency).unwrap_or(u32::MAX);
+            heap.push(Reverse((*frequency, nodes.len())));
+            nodes.push(Some(BinaryTree::new_node_with_data(data, symbol)));
+        }
+
+        while heap.len() > 1 {
+            let Reverse((left_frequency, left)) = heap.pop().unwrap();
+            let Reverse((right_frequency, right)) = heap.pop().unwrap();
+
+            let frequency = left_frequency + right_frequency;
+            let node_ref =
+                BinaryTree::new_node_with_data(u32::try_from(frequency).unwrap_or(u32::MAX), "");
+            {
+                let mut node = node_ref.borrow_mut();
+                node.left = nodes[left].take();
+                node.right = nodes[right].take();
+            }
+            heap.push(Reverse((frequency, nodes.len())));
+            nodes.push(Some(node_ref));
+        }
+
+        let root = heap
+            .pop()
+            .and_then(|Reverse((_, index))| nodes[index].take());
+        if let Some(root) = root.as_ref() {
+            BinaryTree::assign_parents(root);
+        }
+        HuffmanTree {
+            tree: BinaryTree { root },
+        }
+    }
+
+    pub fn encode(&self, symbol: &str) -> Option<Vec<bool>> {
+        let root = self.tree.root.as_ref()?;
+        if Self::is_leaf(root) {
+            // a single symbol still needs one bit per occurrence
+            return (root.borrow().name == symbol).then(|| vec![false]);
+        }
+
+        let mut stack = vec![(root.clone(), Vec::new())];
+        while let Some((node_ref, code)) = stack.pop() {
+            let node = node_ref.borrow();
+            if Self::is_leaf(&node_ref) {
+                if node.name == symbol {
+                    return Some(code);
+                }
+                continue;
+            }
+            for (child, bit) in [(&node.left, false), (&node.right, true)] {
+                if let Some(child) = child {
+                    let mut child_code = code.clone();
+                    child_code.push(bit);
+                    stack.push((child.clone(), child_code));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn decode(&self, bits: &[bool]) -> Option<String> {
+        let root = self.tree.root.as_ref()?;
+        let mut decoded = String::new();
+        if Self::is_leaf(root) {
+            if bits.iter().any(|bit| *bit) {
+                return None;
+            }
+            bits.iter()
+                .for_each(|_| decoded.push_str(&root.borrow().name));
+            return Some(decoded);
+        }
+
+        let mut current = root.clone();
+        for bit in bits {
+            let next = if *bit {
+                current.borrow().right.clone()
+            } else {
+                current.borrow().left.clone()
+            }?;
+            current = next;
+            if Self::is_leaf(&current) {
+                decoded.push_str(&current.borrow().name);
+                current = root.clone();
+            }
+        }
+
+        // the bits must end on a symbol boundary
+        BinaryTree::is_same_node(&current, root).then_some(decoded)
+    }
+
+    fn is_leaf(node_ref: &BinaryTreeNodeRef) -> bool {
+        let node = node_ref.borrow();
+        node.left.is_none() && node.right.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FREQUENCIES: [(&str, usize); 6] = [
+        ("a", 45),
+        ("b", 13),
+        ("c", 12),
+        ("d", 16),
+        ("e", 9),
+        ("f", 5),
+    ];
+
+    #[test]
+    fn build() {
+        let huffman = HuffmanTree::build(&FREQUENCIES);
+        let root = huffman.tree.root.as_ref().unwrap();
+        assert_eq!(root.borrow().data, 100);
+        assert_eq!(BinaryTree::count(root), 2 * FREQUENCIES.len() - 1);
+
+        assert!(HuffmanTree::build(&[]).tree.is_empty());
+    }
+
+    #[test]
+    fn encode() {
+        let huffman = HuffmanTree::build(&FREQUENCIES);
+        let lengths: Vec<_> = FREQUENCIES
+            .iter()
+            .map(|(symbol, _)| huffman.encode(symbol).unwrap().len())
+            .collect();
+        assert_eq!(lengths, [1, 3, 3, 3, 4, 4]);
+
+        // no code is a prefix of another one
+        let codes: Vec<_> = FREQUENCIES
+            .iter()
+            .map(|(symbol, _)| huffman.encode(symbol).unwrap())
+            .collect();
+        for (i, a) in codes.iter().enumerate() {
+            for (j, b) in codes.iter().enumerate() {
+                assert!(i == j || !b.starts_with(a));
+            }
+        }
+
+        assert!(huffman.encode("z").is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        let huffman = HuffmanTree::build(&FREQUENCIES);
+        let message = ["a", "b", "a", "c", "a", "b", "a", "d", "f", "e"];
+        let bits: Vec<_> = message
+            .iter()
+            .flat_map(|symbol| huffman.encode(symbol).unwrap())
+            .collect();
+        assert_eq!(huffman.decode(&bits), Some(message.concat()));
+
+        // cut in the middle of a code
+        assert_eq!(huffman.decode(&bits[..bits.len() - 1]), None);
+    }
+
+    #[test]
+    fn single_symbol() {
+        let huffman = HuffmanTree::build(&[("x", 3)]);
+        assert_eq!(huffman.encode("x"), Some(vec![false]));
+        assert_eq!(huffman.decode(&[false, false]), Some("xx".to_string()));
+        assert_eq!(huffman.decode(&[true]), None);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod generic_tree;
 pub mod binary_tree;
-pub mod binary_search_tree;
\ No newline at end of file
+pub mod binary_search_tree;
+pub mod binary_heap;
+pub mod threaded_binary_tree;
+pub mod expression_tree;
+pub mod huffman_tree;
\ No newline at end of file
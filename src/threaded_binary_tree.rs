@@ -0,0 +1,121 @@
+// https://www.programiz.com/dsa/threaded-binary-tree
+
+use crate::binary_tree::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Right-threaded view of a binary tree: every node without a right child gets a thread
+// to its inorder successor. The threads are kept next to the tree instead of in the
+// `right` links, so the nodes stay untouched and no `Rc` cycles are created.
+pub struct ThreadedBinaryTree {
+    pub tree: BinaryTree,
+    threads: HashMap<Uuid, BinaryTreeNodeRef>,
+}
+
+impl ThreadedBinaryTree {
+    pub fn with_root(root: BinaryTreeNodeRef) -> Self {
+        let mut inorder = BinaryTree::flatten_reverse_inorder(root.clone());
+        inorder.reverse();
+
+        let mut threads = HashMap::new();
+        for pair in inorder.windows(2) {
+            let node = pair[0].borrow();
+            if node.right.is_none() {
+                threads.insert(node.id, pair[1].clone());
+            }
+        }
+
+        ThreadedBinaryTree {
+            tree: BinaryTree::with_root(root),
+            threads,
+        }
+    }
+
+    pub fn thread(&self, node_ref: &BinaryTreeNodeRef) -> Option<BinaryTreeNodeRef> {
+        self.threads.get(&node_ref.borrow().id).cloned()
+    }
+
+    pub fn iter_inorder(&self) -> impl Iterator<Item = BinaryTreeNodeRef> + '_ {
+        let mut current = self
+            .tree
+            .root
+            .as_ref()
+            .map(|root| BinaryTree::leftmost(root).unwrap_or(root.clone()));
+
+        std::iter::from_fn(move || {
+            let node_ref = current.take()?;
+            let right = node_ref.borrow().right.clone();
+            current = match right {
+                Some(right) => Some(BinaryTree::leftmost(&right).unwrap_or(right)),
+                None => self.thread(&node_ref),
+            };
+            Some(node_ref)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_tree::utils::*;
+
+    #[test]
+    fn threads() {
+        let root = populate_balanced_binary_tree();
+        let threaded = ThreadedBinaryTree::with_root(root.clone());
+        let nodes = BinaryTree::flatten_top_down(root);
+
+        // n7 -> n3, n8 -> n1, n10 -> n0, the last node has no successor
+        let thread = |index: usize| {
+            threaded
+                .thread(&nodes[index])
+                .map(|n| n.borrow().name.clone())
+        };
+        assert_eq!(thread(7), Some("n3".to_string()));
+        assert_eq!(thread(8), Some("n1".to_string()));
+        assert_eq!(thread(10), Some("n0".to_string()));
+        assert_eq!(thread(14), None);
+        // nodes with a right child are not threaded
+        assert_eq!(thread(0), None);
+    }
+
+    #[test]
+    fn iter_inorder() {
+        for root in [
+            populate_balanced_binary_tree(),
+            populate_balanced_binary_search_tree(),
+            populate_right_spine(5),
+        ] {
+            let expected: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+                .iter()
+                .map(|n| n.borrow().name.clone())
+                .collect();
+
+            let threaded = ThreadedBinaryTree::with_root(root);
+            let names: Vec<_> = threaded
+                .iter_inorder()
+                .map(|n| n.borrow().name.clone())
+                .collect();
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    fn iter_inorder_without_parents() {
+        let root = populate_balanced_binary_tree();
+        let expected: Vec<_> = BinaryTree::flatten_inorder(root.clone())
+            .iter()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        BinaryTree::flatten_top_down(root.clone())
+            .iter()
+            .for_each(|n| n.borrow_mut().parent = std::rc::Weak::new());
+
+        let threaded = ThreadedBinaryTree::with_root(root);
+        let names: Vec<_> = threaded
+            .iter_inorder()
+            .map(|n| n.borrow().name.clone())
+            .collect();
+        assert_eq!(names, expected);
+    }
+}